@@ -43,6 +43,7 @@ fn setup(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>, mut materials
         RigidBody::Static,
         ClickCatcher {
             offset: Vec3::new(0., 0.5 + 0.05, 0.),
+            ..default()
         }, // Catch mouse click. Offset = (object height + ground height) / 2
         Mesh3d(meshes.add(Cuboid::new(20., 0.1, 20.))),
         Transform::from_xyz(0.0, 0., 0.0),
@@ -2,13 +2,13 @@
 //! You will need to set up your grid with bevy_northstar first.
 //! Consider remove northstar support because its coordinate system and grid size is ambious, unstable and hard to use.
 
-
 use bevy::color::palettes::basic::{
     RED,
     WHITE,
 };
 use bevy::prelude::*;
 use bevy::window::WindowResolution;
+use bevy_movement::grid::PathFallback;
 use bevy_movement::linear::{
     GridInfo,
     LinearMovement,
@@ -58,6 +58,7 @@ fn setup(mut commands: Commands, window: Single<&Window>, mut grid_info: ResMut<
         (window_width / tile_size.x) as u32,
         (window_height / tile_size.y) as u32,
     );
+    grid_info.grid_size = Some(grid_size.extend(1));
 
     // Obstacle
     info!("grid_size: {:?}", grid_size);
@@ -85,6 +86,7 @@ fn setup(mut commands: Commands, window: Single<&Window>, mut grid_info: ResMut<
         AgentPos(UVec3::ZERO),
         Transform::from_translation(grid_info.grid_offset),
         MouseMovementObject::default(), // Move by mouse input
+        PathFallback::NearestReachable, // Move to the nearest reachable tile if clicked tile is unreachable
         Sprite {
             color: RED.into(),
             custom_size: Some(Vec2::new(tile_size.x, tile_size.y)),
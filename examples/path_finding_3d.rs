@@ -67,6 +67,7 @@ fn setup(
     grid_info.tile_size = tile_size;
     grid_info.grid_offset = Vec3::new(-10., -2., 10.);
     let grid_size = UVec3::new(20, 4, 20);
+    grid_info.grid_size = Some(grid_size);
 
     // Obstacle
     for i in 0..grid_size.x {
@@ -122,6 +123,7 @@ fn setup(
         // Catch mouse click
         ClickCatcher {
             offset: Vec3::new(0., 0.5, 0.),
+            ..default()
         },
         Mesh3d(meshes.add(Cuboid::new(20., 1., 20.))),
         Transform::from_xyz(0.0, -0.5, 0.0),
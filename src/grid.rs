@@ -0,0 +1,696 @@
+pub mod flow_field;
+pub mod link;
+pub mod multi_grid;
+pub mod reachable;
+pub mod replan;
+pub mod reservation;
+
+use crate::{
+    Destination,
+    NavChanged,
+    NextDes,
+    PathNotFound,
+};
+use bevy::ecs::system::SystemParam;
+use bevy::platform::collections::{
+    HashMap,
+    HashSet,
+};
+use bevy::prelude::{
+    Add,
+    BVec3,
+    DetectChanges,
+    Commands,
+    Component,
+    Entity,
+    Has,
+    IVec3,
+    Insert,
+    On,
+    Query,
+    Ref,
+    Res,
+    ResMut,
+    Resource,
+    UVec3,
+    Vec3,
+    With,
+};
+use bevy_northstar::prelude::{
+    AgentOfGrid,
+    AgentPos,
+    Grid,
+    Nav,
+    Neighborhood,
+    NextPos,
+    Path,
+    Pathfind,
+    PathfindingFailed,
+};
+use std::cmp::Reverse;
+use std::collections::{
+    BinaryHeap,
+    VecDeque,
+};
+
+/// Maximum number of tiles visited by a grid search when `GridInfo::grid_size` is not set
+pub(crate) const SEARCH_LIMIT: usize = 1 << 16;
+
+/// Mapping between world and tile position of a grid, with nav data copied from the Northstar grid.
+///
+/// The resource is used for agents without [`AgentOfGrid`], and gets nav data of Northstar grid without this component.
+/// For multiple grids, insert this as component to each Northstar grid entity,
+/// and [`AgentOfGrid`] to agents so they find paths on their grid, see [`multi_grid::pathfind_on_grids`].
+#[derive(Resource, Component, Default)]
+pub struct GridInfo {
+    pub tile_size: Vec3,
+    pub grid_offset: Vec3,
+    /// Number of tiles on each axis. If `None`, only positions before `grid_offset` are out of the grid.
+    /// It's set to dimensions of the Northstar grid when nav data is copied.
+    pub grid_size: Option<UVec3>,
+    /// Clamp out-of-grid positions to the nearest tile instead of rejecting them
+    pub clamp_to_grid: bool,
+    /// Speed multiplier of tiles, e.g. `0.5` for mud or `1.5` for road. Tiles not in this map use `1.0`.
+    pub speed_multipliers: HashMap<UVec3, f32>,
+    /// Copied by [`sync_grid_nav`]. Before that, all tiles are passable with cost `1` and cardinal neighbors.
+    nav: Option<GridNav>,
+}
+
+/// Nav data of a Northstar grid
+#[derive(Clone, Default)]
+struct GridNav {
+    size: UVec3,
+    /// Cost to enter each tile, `None` if impassable
+    costs: Vec<Option<u32>>,
+    /// Adjacent neighbors of each tile, as bits of [`offset_bit`]
+    neighbors: Vec<u32>,
+    /// Neighbors through portals
+    portals: HashMap<UVec3, Vec<UVec3>>,
+    /// Tiles with a portal to each tile
+    portal_sources: HashMap<UVec3, Vec<UVec3>>,
+    /// Lowest cost of passable tiles
+    min_cost: u32,
+}
+
+impl GridNav {
+    fn index(&self, tile: UVec3) -> Option<usize> {
+        tile.cmplt(self.size)
+            .all()
+            .then(|| (tile.x + (tile.y + tile.z * self.size.y) * self.size.x) as usize)
+    }
+}
+
+/// Bit of an adjacent offset in neighbor masks
+const fn offset_bit(offset: IVec3) -> u32 {
+    1 << ((offset.x + 1) + (offset.y + 1) * 3 + (offset.z + 1) * 9)
+}
+
+/// Cardinal neighbors when there is no nav data. The z axis is ignored with `2d` feature.
+const CARDINAL_NEIGHBORS: u32 = {
+    let bits = offset_bit(IVec3::X) | offset_bit(IVec3::NEG_X) | offset_bit(IVec3::Y) | offset_bit(IVec3::NEG_Y);
+    if cfg!(feature = "2d") {
+        bits
+    } else {
+        bits | offset_bit(IVec3::Z) | offset_bit(IVec3::NEG_Z)
+    }
+};
+
+impl GridInfo {
+    /// Convert world position to tile position.
+    /// Return `None` if the position is out of the grid and `clamp_to_grid` is disabled.
+    pub fn world_to_tile(&self, pos: Vec3) -> Option<UVec3> {
+        let (tile_pos, max) = self.locate(pos);
+        let in_grid = tile_pos.cmpge(Vec3::ZERO).all() && tile_pos.cmple(max).all();
+        if !in_grid && !self.clamp_to_grid {
+            return None;
+        }
+
+        let tile_pos = tile_pos.clamp(Vec3::ZERO, max);
+        Some(UVec3::new(tile_pos.x as u32, tile_pos.y as u32, tile_pos.z as u32))
+    }
+
+    /// Check if world position is inside the grid, regardless of `clamp_to_grid`
+    pub fn contains_world(&self, pos: Vec3) -> bool {
+        let (tile_pos, max) = self.locate(pos);
+        tile_pos.cmpge(Vec3::ZERO).all() && tile_pos.cmple(max).all()
+    }
+
+    /// Return unclamped tile position and the last tile position of the grid
+    fn locate(&self, pos: Vec3) -> (Vec3, Vec3) {
+        let tile_pos = ((pos - self.grid_offset) / self.tile_size).round();
+        // Axis with zero tile size is ignored
+        let tile_pos = Vec3::select(tile_pos.is_finite_mask(), tile_pos, Vec3::ZERO);
+        let max = self
+            .grid_size
+            .map(|size| size.saturating_sub(UVec3::ONE).as_vec3())
+            .unwrap_or(Vec3::INFINITY);
+        (tile_pos, max)
+    }
+
+    /// Convert tile position to world position of the tile center
+    pub fn tile_to_world(&self, tile: UVec3) -> Vec3 {
+        tile.as_vec3() * self.tile_size + self.grid_offset
+    }
+
+    /// Destination at the tile center.
+    /// If the tile has a speed multiplier, `custom_velocity` is set to `speed` multiplied by it.
+    pub fn tile_destination(&self, tile: UVec3, speed: f32) -> Destination {
+        Destination {
+            pos: self.tile_to_world(tile),
+            custom_velocity: self.speed_multipliers.get(&tile).map(|multiplier| speed * multiplier),
+        }
+    }
+
+    pub fn contains(&self, tile: UVec3) -> bool {
+        self.grid_size.is_none_or(|size| tile.cmplt(size).all())
+    }
+
+    pub fn is_passable(&self, tile: UVec3) -> bool {
+        self.cost(tile).is_some()
+    }
+
+    /// Cost to enter the tile. Return `None` if the tile is impassable.
+    pub fn cost(&self, tile: UVec3) -> Option<u32> {
+        if !self.contains(tile) {
+            return None;
+        }
+        match &self.nav {
+            Some(nav) => nav.index(tile).and_then(|i| nav.costs[i]),
+            None => Some(1),
+        }
+    }
+
+    /// Tiles which can be moved to from the tile, decided by the neighborhood of the Northstar grid
+    pub fn neighbors(&self, tile: UVec3) -> impl Iterator<Item = UVec3> + '_ {
+        let (bits, portals) = match &self.nav {
+            Some(nav) => (
+                nav.index(tile).map_or(0, |i| nav.neighbors[i]),
+                nav.portals.get(&tile).map_or(&[][..], Vec::as_slice),
+            ),
+            None => (CARDINAL_NEIGHBORS, &[][..]),
+        };
+        (0..27)
+            .filter(move |i| bits & (1 << i) != 0)
+            .filter_map(move |i| tile.checked_add_signed(IVec3::new(i % 3, i / 3 % 3, i / 9) - IVec3::ONE))
+            .chain(portals.iter().copied())
+            .filter(|neighbor| self.contains(*neighbor))
+    }
+
+    /// Tiles which can move to the tile, the reverse of [`Self::neighbors`]
+    pub fn predecessors(&self, tile: UVec3) -> impl Iterator<Item = UVec3> + '_ {
+        let sources = self
+            .nav
+            .as_ref()
+            .and_then(|nav| nav.portal_sources.get(&tile))
+            .map_or(&[][..], Vec::as_slice);
+        (0..27)
+            .filter_map(move |i| {
+                let offset = IVec3::new(i % 3, i / 3 % 3, i / 9) - IVec3::ONE;
+                let predecessor = tile.checked_add_signed(-offset)?;
+                let bits = match &self.nav {
+                    Some(nav) => nav.index(predecessor).map_or(0, |i| nav.neighbors[i]),
+                    None => CARDINAL_NEIGHBORS,
+                };
+                (bits & offset_bit(offset) != 0).then_some(predecessor)
+            })
+            .chain(sources.iter().copied())
+            .filter(|predecessor| self.contains(*predecessor))
+    }
+
+    /// Copy nav data from the Northstar grid, which should be built. Return tiles whose cost is changed.
+    pub fn copy_nav<N: Neighborhood>(&mut self, grid: &Grid<N>) -> Vec<UVec3> {
+        let size = grid.dimensions();
+        let count = (size.x * size.y * size.z) as usize;
+        let mut nav = GridNav {
+            size,
+            costs: vec![None; count],
+            neighbors: vec![0; count],
+            portals: HashMap::new(),
+            portal_sources: HashMap::new(),
+            min_cost: 0,
+        };
+        for ((x, y, z), cell) in grid.view().indexed_iter() {
+            let tile = UVec3::new(x as u32, y as u32, z as u32);
+            let Some(i) = nav.index(tile) else {
+                continue;
+            };
+            nav.costs[i] = match cell.nav() {
+                Nav::Passable(cost) => Some(cost),
+                Nav::Portal(portal) => Some(portal.cost),
+                Nav::Impassable => None,
+            };
+            for neighbor in cell.neighbor_iter(tile) {
+                let offset = neighbor.as_ivec3() - tile.as_ivec3();
+                if offset.abs().max_element() <= 1 {
+                    nav.neighbors[i] |= offset_bit(offset);
+                } else {
+                    nav.portals.entry(tile).or_default().push(neighbor);
+                    nav.portal_sources.entry(neighbor).or_default().push(tile);
+                }
+            }
+        }
+
+        nav.min_cost = nav.costs.iter().flatten().min().copied().unwrap_or(0);
+
+        let is_resized = self.grid_size != Some(size);
+        let changed = (0..size.z)
+            .flat_map(|z| (0..size.y).flat_map(move |y| (0..size.x).map(move |x| UVec3::new(x, y, z))))
+            .filter(|tile| is_resized || nav.index(*tile).is_some_and(|i| nav.costs[i] != self.cost(*tile)))
+            .collect();
+        self.grid_size = Some(size);
+        self.nav = Some(nav);
+        changed
+    }
+
+    /// Find the tile nearest to `goal` that can be reached from `start`
+    pub fn nearest_reachable(&self, start: UVec3, goal: UVec3) -> Option<UVec3> {
+        self.nearest_reachable_by(start, goal, |_| true)
+    }
+
+    /// Find the tile nearest to `goal` that can be reached from `start` and is accepted by `filter`
+    pub fn nearest_reachable_by(&self, start: UVec3, goal: UVec3, filter: impl Fn(UVec3) -> bool) -> Option<UVec3> {
+        if !self.is_passable(start) {
+            return None;
+        }
+
+        let goal = goal.as_vec3();
+        let mut best: Option<(UVec3, f32)> = None;
+        let mut visited = HashSet::from([start]);
+        let mut queue = VecDeque::from([start]);
+        while let Some(tile) = queue.pop_front() {
+            let distance = tile.as_vec3().distance_squared(goal);
+            if filter(tile) && best.is_none_or(|(_, best)| distance < best) {
+                best = Some((tile, distance));
+            }
+            if self.grid_size.is_none() && visited.len() >= SEARCH_LIMIT {
+                continue;
+            }
+            for neighbor in self.neighbors(tile) {
+                if self.is_passable(neighbor) && visited.insert(neighbor) {
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+
+        best.map(|(tile, _)| tile)
+    }
+
+    /// Cheapest path from `start` to `goal`, both included. Return `None` if `goal` can't be reached.
+    /// Northstar may choose a different path with the same cost.
+    pub fn find_path(&self, start: UVec3, goal: UVec3) -> Option<Vec<UVec3>> {
+        if !self.is_passable(start) || !self.is_passable(goal) {
+            return None;
+        }
+
+        let heuristic = |tile: UVec3| self.cost_lower_bound(tile, goal);
+        // Cost from start and previous tile
+        let mut tiles = HashMap::from([(start, (0, start))]);
+        let mut heap = BinaryHeap::from([Reverse((heuristic(start), 0, start.to_array()))]);
+        while let Some(Reverse((_, cost, tile))) = heap.pop() {
+            let tile = UVec3::from_array(tile);
+            if tiles.get(&tile).is_none_or(|(c, _)| *c != cost) {
+                continue;
+            }
+            if tile == goal {
+                let mut path = vec![goal];
+                let mut current = goal;
+                while current != start {
+                    current = tiles[&current].1;
+                    path.push(current);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            if self.grid_size.is_none() && tiles.len() >= SEARCH_LIMIT {
+                continue;
+            }
+
+            for neighbor in self.neighbors(tile) {
+                let Some(step) = self.cost(neighbor) else {
+                    continue;
+                };
+                let new_cost = cost + step;
+                if tiles.get(&neighbor).is_none_or(|(old_cost, _)| new_cost < *old_cost) {
+                    tiles.insert(neighbor, (new_cost, tile));
+                    heap.push(Reverse((new_cost + heuristic(neighbor), new_cost, neighbor.to_array())));
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Lower bound of the cost to move between tiles, which keeps A* optimal.
+    /// Each step moves at most one tile on each axis, so it needs at least the Chebyshev distance of steps.
+    fn cost_lower_bound(&self, from: UVec3, to: UVec3) -> u32 {
+        let steps = (from.as_ivec3() - to.as_ivec3()).abs().max_element() as u32;
+        match &self.nav {
+            // Portals can move any distance in one step
+            Some(nav) if !nav.portals.is_empty() => 0,
+            Some(nav) => steps * nav.min_cost,
+            None => steps,
+        }
+    }
+
+    /// Check if the straight line between two tile centers only goes through passable tiles.
+    /// Moving diagonally through a corner requires both tiles beside the corner to be passable.
+    pub fn line_of_sight(&self, from: UVec3, to: UVec3) -> bool {
+        if !self.is_passable(from) {
+            return false;
+        }
+
+        let step = (to.as_ivec3() - from.as_ivec3()).signum();
+        line_tiles(from, to).all(|(tile, crossed)| {
+            if crossed.bitmask().count_ones() > 1 {
+                // Through a corner
+                let previous = tile.as_ivec3() - IVec3::select(crossed, step, IVec3::ZERO);
+                for axis in (0..3).filter(|axis| crossed.test(*axis)) {
+                    let mut side = previous;
+                    side[axis] += step[axis];
+                    if !self.is_passable(side.as_uvec3()) {
+                        return false;
+                    }
+                }
+            }
+            self.is_passable(tile)
+        })
+    }
+
+    /// Cost to move straight between two tile centers, which is the sum of costs of tiles entered on the way.
+    /// Return `None` if the line goes through an impassable tile.
+    pub fn line_cost(&self, from: UVec3, to: UVec3) -> Option<u32> {
+        line_tiles(from, to).map(|(tile, _)| self.cost(tile)).sum()
+    }
+
+    /// Remove waypoints that can be skipped by moving straight to a later waypoint
+    pub fn smooth_path(&self, path: &[UVec3]) -> Vec<UVec3> {
+        let Some((first, rest)) = path.split_first() else {
+            return Vec::new();
+        };
+
+        let mut smoothed = vec![*first];
+        let mut anchor = *first;
+        for (i, tile) in rest.iter().enumerate() {
+            // `path[i]` is the waypoint before `tile`
+            if !self.line_of_sight(anchor, *tile) && path[i] != anchor {
+                anchor = path[i];
+                smoothed.push(anchor);
+            }
+        }
+        if let Some(last) = rest.last() {
+            smoothed.push(*last);
+        }
+
+        smoothed
+    }
+}
+
+/// Tiles crossed by the straight line between two tile centers after `from`, with axes crossed to enter each tile.
+/// Crossing more than one axis at once goes through a corner.
+fn line_tiles(from: UVec3, to: UVec3) -> impl Iterator<Item = (UVec3, BVec3)> {
+    let delta = to.as_ivec3() - from.as_ivec3();
+    let step = delta.signum();
+    // Distance (in fraction of the line) to cross one tile, and to cross the next tile boundary
+    let t_delta = Vec3::ONE / delta.abs().as_vec3();
+    let mut t_max = t_delta * 0.5;
+    let mut tile = from.as_ivec3();
+
+    std::iter::from_fn(move || {
+        if tile == to.as_ivec3() {
+            return None;
+        }
+        let t = t_max.min_element();
+        let crossed = t_max.cmple(Vec3::splat(t + 1e-6));
+        for axis in (0..3).filter(|axis| crossed.test(*axis)) {
+            tile[axis] += step[axis];
+            t_max[axis] += t_delta[axis];
+        }
+        Some((tile.as_uvec3(), crossed))
+    })
+}
+
+/// Mark the grid layer shown to player.
+/// When grid layers overlap, e.g. floors with `2d` feature, clicks go to the active one.
+#[derive(Component, Default, Clone, Copy, Debug)]
+pub struct ActiveGrid;
+
+/// Look up [`GridInfo`] of grid entities, fall back to the `GridInfo` resource
+#[derive(SystemParam)]
+pub struct Grids<'w, 's> {
+    default: Res<'w, GridInfo>,
+    grids: Query<'w, 's, (Entity, &'static GridInfo, Has<ActiveGrid>)>,
+    agents: Query<'w, 's, &'static AgentOfGrid>,
+}
+
+impl Grids<'_, '_> {
+    /// Grid entity that agent belongs to
+    pub fn grid_of(&self, agent: Entity) -> Option<Entity> {
+        self.agents.get(agent).ok().map(|agent_of_grid| agent_of_grid.0)
+    }
+
+    /// Grid that agent navigates on
+    pub fn of_agent(&self, agent: Entity) -> &GridInfo {
+        self.get(self.grid_of(agent))
+    }
+
+    /// `GridInfo` of grid entity, or the resource if `grid` is `None` or has no `GridInfo`
+    pub fn get(&self, grid: Option<Entity>) -> &GridInfo {
+        grid.and_then(|grid| self.grids.get(grid).ok())
+            .map_or(&self.default, |(_, grid_info, _)| grid_info)
+    }
+
+    /// Grid entity whose `GridInfo` is used for the grid, `None` if the resource is used
+    pub fn info_owner(&self, grid: Option<Entity>) -> Option<Entity> {
+        grid.filter(|grid| self.grids.contains(*grid))
+    }
+
+    /// Grid entity which contains the world position. [`ActiveGrid`] is preferred.
+    pub fn under(&self, pos: Vec3) -> Option<Entity> {
+        self.grids
+            .iter()
+            .filter(|(_, grid_info, _)| grid_info.contains_world(pos))
+            .max_by_key(|(_, _, is_active)| *is_active)
+            .map(|(entity, ..)| entity)
+    }
+}
+
+/// Copy nav data of Northstar grids with neighborhood `N` to their [`GridInfo`] when the grids change.
+/// Grids without `GridInfo` component are copied to the `GridInfo` resource.
+///
+/// It's added to `PreUpdate` for Northstar's neighborhoods. Add it for your custom neighborhood.
+#[allow(clippy::type_complexity)]
+pub fn sync_grid_nav<N: Neighborhood + 'static>(
+    mut commands: Commands,
+    mut grids: Query<(Entity, Ref<Grid<N>>, Option<&mut GridInfo>)>,
+    mut default: ResMut<GridInfo>,
+) {
+    for (entity, grid, grid_info) in grids.iter_mut() {
+        if !grid.is_changed() {
+            continue;
+        }
+
+        let (owner, tiles) = match grid_info {
+            Some(mut grid_info) => (Some(entity), grid_info.copy_nav(&grid)),
+            None => (None, default.copy_nav(&grid)),
+        };
+        if !tiles.is_empty() {
+            commands.trigger(NavChanged { grid: owner, tiles });
+        }
+    }
+}
+
+/// What to do when there is no path to the goal
+#[derive(Component, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PathFallback {
+    /// Stay where it is
+    #[default]
+    Nothing,
+    /// Move to the reachable tile nearest to the goal
+    NearestReachable,
+    /// Ignore the grid and move straight to the goal
+    StraightLine,
+}
+
+/// Skip waypoints on the grid when the agent can move straight to a later one.
+///
+/// Applied to Northstar's `Path` when it's inserted and to the next tile chosen from [`flow_field::FlowField`].
+/// Destinations set directly on [`crate::linear::LinearMovement`] are left as they are.
+/// Speed multipliers of skipped tiles are not applied.
+#[derive(Component, Default, Clone, Copy, Debug)]
+pub struct SmoothPath;
+
+pub(crate) fn smooth_northstar_path(
+    trigger: On<Insert, Path>,
+    mut query: Query<(&AgentPos, &mut Path), With<SmoothPath>>,
+    grids: Grids,
+) {
+    let entity = trigger.entity;
+    let Ok((agent_pos, mut path)) = query.get_mut(entity) else {
+        return;
+    };
+
+    let start = agent_pos.0;
+    let tiles: Vec<UVec3> = std::iter::once(start)
+        .chain(path.path().iter().copied().skip_while(|tile| *tile == start))
+        .collect();
+    let smoothed = grids.of_agent(entity).smooth_path(&tiles);
+    if smoothed.len() < tiles.len() {
+        let cost = path.cost();
+        *path = Path::new(smoothed[1..].to_vec(), cost);
+    }
+}
+
+pub(crate) fn pathfinding_failed(
+    trigger: On<Add, PathfindingFailed>,
+    mut commands: Commands,
+    query: Query<(&Pathfind, &AgentPos, Option<&PathFallback>)>,
+    grids: Grids,
+) {
+    let entity = trigger.entity;
+    commands.entity(entity).remove::<PathfindingFailed>();
+    let Ok((pathfind, agent_pos, fallback)) = query.get(entity) else {
+        return;
+    };
+    let grid_info = grids.of_agent(entity);
+
+    let goal = pathfind.goal;
+    commands.entity(entity).remove::<Pathfind>();
+
+    let fallback = match fallback.copied().unwrap_or_default() {
+        PathFallback::Nothing => None,
+        PathFallback::NearestReachable => grid_info
+            .nearest_reachable(agent_pos.0, goal)
+            .filter(|tile| *tile != agent_pos.0 && *tile != goal)
+            .map(|tile| {
+                commands.entity(entity).insert(Pathfind::new(tile));
+                grid_info.tile_to_world(tile)
+            }),
+        PathFallback::StraightLine => {
+            let pos = grid_info.tile_to_world(goal);
+            commands.entity(entity).remove::<NextPos>();
+            commands.trigger(NextDes {
+                entity,
+                des: Destination::from_pos(pos),
+                is_chain: false,
+            });
+            Some(pos)
+        }
+    };
+
+    commands.trigger(PathNotFound {
+        entity,
+        pos: grid_info.tile_to_world(goal),
+        fallback,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_northstar::prelude::{
+        CardinalGrid,
+        GridSettingsBuilder,
+        OrdinalGrid,
+    };
+
+    /// Flat 8x8 grid with blocked tiles
+    fn grid_info(blocked: &[(u32, u32)]) -> GridInfo {
+        let mut grid = CardinalGrid::new(&GridSettingsBuilder::new_2d(8, 8).chunk_size(4).build());
+        for (x, y) in blocked {
+            grid.set_nav(UVec3::new(*x, *y, 0), Nav::Impassable);
+        }
+        grid.build();
+        let mut grid_info = GridInfo::default();
+        grid_info.copy_nav(&grid);
+        grid_info
+    }
+
+    fn tile(x: u32, y: u32) -> UVec3 {
+        UVec3::new(x, y, 0)
+    }
+
+    #[test]
+    fn line_of_sight_straight() {
+        let grid_info = grid_info(&[(3, 1)]);
+        assert!(grid_info.line_of_sight(tile(0, 0), tile(7, 0)));
+        assert!(grid_info.line_of_sight(tile(2, 0), tile(2, 7)));
+        assert!(!grid_info.line_of_sight(tile(0, 1), tile(7, 1)));
+        assert!(!grid_info.line_of_sight(tile(0, 0), tile(3, 1)));
+    }
+
+    #[test]
+    fn line_of_sight_diagonal_corners() {
+        assert!(grid_info(&[]).line_of_sight(tile(0, 0), tile(3, 3)));
+        // Moving through the corner between (0, 0) and (1, 1) needs both (1, 0) and (0, 1)
+        assert!(!grid_info(&[(1, 0)]).line_of_sight(tile(0, 0), tile(3, 3)));
+        assert!(!grid_info(&[(0, 1)]).line_of_sight(tile(0, 0), tile(3, 3)));
+        assert!(!grid_info(&[(2, 1)]).line_of_sight(tile(3, 3), tile(0, 0)));
+    }
+
+    #[test]
+    fn line_of_sight_side_tiles() {
+        // The line from (0, 0) to (4, 2) goes through (1, 0), (1, 1), (2, 1), (3, 1) and (3, 2)
+        assert!(grid_info(&[(2, 0), (1, 2), (3, 0), (2, 2)]).line_of_sight(tile(0, 0), tile(4, 2)));
+        assert!(!grid_info(&[(2, 1)]).line_of_sight(tile(0, 0), tile(4, 2)));
+        assert!(!grid_info(&[(1, 0)]).line_of_sight(tile(4, 2), tile(0, 0)));
+    }
+
+    #[test]
+    fn line_of_sight_out_of_grid() {
+        let grid_info = grid_info(&[]);
+        assert!(!grid_info.line_of_sight(tile(0, 0), tile(8, 0)));
+        assert!(!grid_info.line_of_sight(tile(0, 0), UVec3::new(0, 0, 1)));
+    }
+
+    #[test]
+    fn smooth_path_open() {
+        let path = [tile(0, 0), tile(1, 0), tile(2, 0), tile(2, 1), tile(2, 2)];
+        assert_eq!(grid_info(&[]).smooth_path(&path), vec![tile(0, 0), tile(2, 2)]);
+    }
+
+    #[test]
+    fn smooth_path_around_corner() {
+        let path = [tile(0, 0), tile(1, 0), tile(2, 0), tile(2, 1), tile(2, 2)];
+        assert_eq!(
+            grid_info(&[(1, 1)]).smooth_path(&path),
+            vec![tile(0, 0), tile(2, 0), tile(2, 2)]
+        );
+    }
+
+    #[test]
+    fn smooth_path_short() {
+        let grid_info = grid_info(&[]);
+        assert_eq!(grid_info.smooth_path(&[]), Vec::<UVec3>::new());
+        assert_eq!(grid_info.smooth_path(&[tile(1, 1)]), vec![tile(1, 1)]);
+        assert_eq!(
+            grid_info.smooth_path(&[tile(1, 1), tile(2, 1)]),
+            vec![tile(1, 1), tile(2, 1)]
+        );
+    }
+
+    #[test]
+    fn find_path_is_cheapest() {
+        let mut rng = fastrand::Rng::with_seed(7);
+        for _ in 0..200 {
+            let mut grid = OrdinalGrid::new(&GridSettingsBuilder::new_2d(8, 8).chunk_size(4).build());
+            for y in 0..8 {
+                for x in 0..8 {
+                    let nav = if rng.u8(0..10) == 0 { Nav::Impassable } else { Nav::Passable(rng.u32(1..=5)) };
+                    grid.set_nav(tile(x, y), nav);
+                }
+            }
+            grid.build();
+            let mut grid_info = GridInfo::default();
+            grid_info.copy_nav(&grid);
+
+            let start = tile(rng.u32(0..8), rng.u32(0..8));
+            let area = reachable::ReachableArea::new(&grid_info, start, u32::MAX);
+            for goal in (0..8).flat_map(|y| (0..8).map(move |x| tile(x, y))) {
+                let cost = grid_info
+                    .find_path(start, goal)
+                    .map(|path| path[1..].iter().map(|tile| grid_info.cost(*tile).unwrap()).sum::<u32>());
+                assert_eq!(cost, area.cost(goal), "from {start} to {goal}");
+            }
+        }
+    }
+}
@@ -0,0 +1,354 @@
+use crate::grid::{
+    GridInfo,
+    Grids,
+    SmoothPath,
+    SEARCH_LIMIT,
+};
+use crate::linear::LinearMovement;
+use crate::{
+    NavChanged,
+    PathNotFound,
+};
+use bevy::platform::collections::{
+    HashMap,
+    HashSet,
+};
+use bevy::prelude::{
+    Commands,
+    Component,
+    Entity,
+    Has,
+    On,
+    Query,
+    Res,
+    ResMut,
+    Resource,
+    UVec3,
+};
+use bevy_northstar::prelude::AgentPos;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// Move to the goal tile by following the shared flow field instead of per-agent path finding.
+/// All agents with the same goal on the same grid share one flow field.
+///
+/// When the goal is unreachable, `PathNotFound` is triggered and [`super::PathFallback`] is not applied.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct FlowFieldGoal(pub UVec3);
+
+/// Integration field of a goal: total cost and next tile to move from each reachable tile
+pub struct FlowField {
+    pub goal: UVec3,
+    flow: HashMap<UVec3, (u32, UVec3)>,
+}
+
+impl FlowField {
+    pub fn new(grid_info: &GridInfo, goal: UVec3) -> Self {
+        let mut field = Self {
+            goal,
+            flow: HashMap::new(),
+        };
+        if grid_info.is_passable(goal) {
+            field.flow.insert(goal, (0, goal));
+            field.expand(grid_info, vec![goal]);
+        }
+        field
+    }
+
+    /// Total cost to move from tile to the goal
+    pub fn cost(&self, tile: UVec3) -> Option<u32> {
+        self.flow.get(&tile).map(|(cost, _)| *cost)
+    }
+
+    /// Next tile to move from tile toward the goal
+    pub fn next(&self, tile: UVec3) -> Option<UVec3> {
+        self.flow.get(&tile).map(|(_, next)| *next)
+    }
+
+    /// Furthest tile along the flow which can be reached in a straight line from tile
+    pub fn next_in_sight(&self, grid_info: &GridInfo, tile: UVec3) -> Option<UVec3> {
+        let mut next = self.next(tile)?;
+        while let Some(further) = self.next(next).filter(|further| *further != next) {
+            if !grid_info.line_of_sight(tile, further) {
+                break;
+            }
+            next = further;
+        }
+        Some(next)
+    }
+
+    /// Update the field with changed tiles only
+    fn update(&mut self, grid_info: &GridInfo, changed: &[UVec3]) {
+        if changed.contains(&self.goal) {
+            *self = Self::new(grid_info, self.goal);
+            return;
+        }
+
+        // Tiles moving through changed tiles are invalid
+        let mut children: HashMap<UVec3, Vec<UVec3>> = HashMap::new();
+        for (tile, (_, next)) in self.flow.iter() {
+            children.entry(*next).or_default().push(*tile);
+        }
+        let mut invalid = changed.to_vec();
+        let mut removed = Vec::new();
+        while let Some(tile) = invalid.pop() {
+            if self.flow.remove(&tile).is_some() {
+                removed.push(tile);
+                invalid.extend(children.remove(&tile).unwrap_or_default());
+            }
+        }
+
+        // Search again from valid tiles which removed and changed tiles can move to
+        let seeds: HashSet<UVec3> = removed
+            .iter()
+            .chain(changed.iter())
+            .flat_map(|tile| grid_info.neighbors(*tile))
+            .filter(|tile| self.flow.contains_key(tile))
+            .collect();
+        self.expand(grid_info, seeds.into_iter().collect());
+    }
+
+    fn expand(&mut self, grid_info: &GridInfo, seeds: Vec<UVec3>) {
+        let mut heap: BinaryHeap<_> = seeds
+            .into_iter()
+            .filter_map(|tile| self.cost(tile).map(|cost| Reverse((cost, tile.to_array()))))
+            .collect();
+
+        while let Some(Reverse((cost, tile))) = heap.pop() {
+            let tile = UVec3::from_array(tile);
+            if self.cost(tile) != Some(cost) {
+                continue;
+            }
+            let Some(step) = grid_info.cost(tile) else {
+                continue;
+            };
+            if grid_info.grid_size.is_none() && self.flow.len() >= SEARCH_LIMIT {
+                continue;
+            }
+
+            // Search backward, through tiles which can move to this tile
+            for predecessor in grid_info.predecessors(tile) {
+                if !grid_info.is_passable(predecessor) {
+                    continue;
+                }
+                let new_cost = cost + step;
+                if self.cost(predecessor).is_none_or(|old_cost| new_cost < old_cost) {
+                    self.flow.insert(predecessor, (new_cost, tile));
+                    heap.push(Reverse((new_cost, predecessor.to_array())));
+                }
+            }
+        }
+    }
+}
+
+/// Flow fields in use, by grid entity and goal tile
+#[derive(Resource, Default)]
+pub struct FlowFields {
+    fields: HashMap<(Option<Entity>, UVec3), FlowField>,
+}
+
+impl FlowFields {
+    pub fn get(&self, grid: Option<Entity>, goal: UVec3) -> Option<&FlowField> {
+        self.fields.get(&(grid, goal))
+    }
+}
+
+pub(crate) fn update_flow_fields(
+    mut flow_fields: ResMut<FlowFields>,
+    query: Query<(Entity, &FlowFieldGoal)>,
+    grids: Grids,
+) {
+    let in_use: HashSet<(Option<Entity>, UVec3)> = query
+        .iter()
+        .map(|(entity, goal)| (grids.grid_of(entity), goal.0))
+        .collect();
+    flow_fields.fields.retain(|key, _| in_use.contains(key));
+
+    for (grid, goal) in in_use {
+        let grid_info = grids.get(grid);
+        flow_fields
+            .fields
+            .entry((grid, goal))
+            .or_insert_with(|| FlowField::new(grid_info, goal));
+    }
+}
+
+pub(crate) fn update_flow_fields_on_nav_change(
+    trigger: On<NavChanged>,
+    mut flow_fields: ResMut<FlowFields>,
+    grids: Grids,
+) {
+    for ((grid, _), field) in flow_fields.fields.iter_mut() {
+        if grids.info_owner(*grid) == trigger.grid {
+            field.update(grids.get(*grid), &trigger.tiles);
+        }
+    }
+}
+
+pub(crate) fn flow_field_travel(
+    mut commands: Commands,
+    mut query: Query<(Entity, &FlowFieldGoal, &AgentPos, &mut LinearMovement, Has<SmoothPath>)>,
+    flow_fields: Res<FlowFields>,
+    grids: Grids,
+) {
+    for (entity, goal, agent_pos, mut movement, smooth) in query.iter_mut() {
+        // Still moving to the next tile
+        if !movement.des.is_empty() || movement.is_freezed {
+            continue;
+        }
+
+        if agent_pos.0 == goal.0 {
+            commands.entity(entity).remove::<FlowFieldGoal>();
+            continue;
+        }
+
+        let grid = grids.grid_of(entity);
+        let grid_info = grids.get(grid);
+        // Flow field is not computed yet
+        let Some(field) = flow_fields.get(grid, goal.0) else {
+            continue;
+        };
+        let next = if smooth { field.next_in_sight(grid_info, agent_pos.0) } else { field.next(agent_pos.0) };
+        match next {
+            Some(next) => {
+                let des = grid_info.tile_destination(next, movement.speed);
+                movement.des.push(des);
+            }
+            None => {
+                commands.entity(entity).remove::<FlowFieldGoal>();
+                commands.trigger(PathNotFound {
+                    entity,
+                    pos: grid_info.tile_to_world(goal.0),
+                    fallback: None,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::reachable::ReachableArea;
+    use bevy_northstar::prelude::{
+        CardinalGrid,
+        GridSettingsBuilder,
+        Nav,
+        Portal,
+    };
+
+    const SIZE: u32 = 8;
+
+    fn grid() -> CardinalGrid {
+        let mut grid = CardinalGrid::new(&GridSettingsBuilder::new_2d(SIZE, SIZE).chunk_size(4).build());
+        for y in 1..SIZE - 1 {
+            grid.set_nav(UVec3::new(4, y, 0), Nav::Impassable);
+        }
+        grid.build();
+        grid
+    }
+
+    fn costs(field: &FlowField) -> Vec<Option<u32>> {
+        (0..SIZE)
+            .flat_map(|y| (0..SIZE).map(move |x| UVec3::new(x, y, 0)))
+            .map(|tile| field.cost(tile))
+            .collect()
+    }
+
+    /// Apply `edit` to the grid, then check the updated field against a fresh one
+    fn assert_update(goal: UVec3, edit: impl FnOnce(&mut CardinalGrid)) {
+        let mut grid = grid();
+        let mut grid_info = GridInfo::default();
+        grid_info.copy_nav(&grid);
+        let mut field = FlowField::new(&grid_info, goal);
+
+        edit(&mut grid);
+        grid.build();
+        let changed = grid_info.copy_nav(&grid);
+        field.update(&grid_info, &changed);
+
+        assert_eq!(costs(&field), costs(&FlowField::new(&grid_info, goal)));
+    }
+
+    #[test]
+    fn block_tile_on_flow() {
+        assert_update(UVec3::new(7, 7, 0), |grid| {
+            grid.set_nav(UVec3::new(4, 7, 0), Nav::Impassable);
+        });
+    }
+
+    #[test]
+    fn block_tile_off_flow() {
+        assert_update(UVec3::new(7, 7, 0), |grid| {
+            grid.set_nav(UVec3::new(0, 0, 0), Nav::Impassable);
+        });
+    }
+
+    #[test]
+    fn open_tile() {
+        assert_update(UVec3::new(7, 3, 0), |grid| {
+            grid.set_nav(UVec3::new(4, 3, 0), Nav::Passable(1));
+        });
+    }
+
+    #[test]
+    fn block_and_open_tiles() {
+        assert_update(UVec3::new(7, 3, 0), |grid| {
+            grid.set_nav(UVec3::new(4, 0, 0), Nav::Impassable);
+            grid.set_nav(UVec3::new(4, 5, 0), Nav::Passable(1));
+        });
+    }
+
+    #[test]
+    fn change_cost() {
+        assert_update(UVec3::new(7, 7, 0), |grid| {
+            grid.set_nav(UVec3::new(5, 7, 0), Nav::Passable(5));
+            grid.set_nav(UVec3::new(2, 0, 0), Nav::Passable(3));
+        });
+    }
+
+    #[test]
+    fn block_and_open_goal() {
+        let goal = UVec3::new(7, 7, 0);
+        assert_update(goal, |grid| grid.set_nav(goal, Nav::Impassable));
+
+        let mut grid = grid();
+        grid.set_nav(goal, Nav::Impassable);
+        grid.build();
+        let mut grid_info = GridInfo::default();
+        grid_info.copy_nav(&grid);
+        let mut field = FlowField::new(&grid_info, goal);
+        assert_eq!(field.cost(UVec3::ZERO), None);
+
+        grid.set_nav(goal, Nav::Passable(1));
+        grid.build();
+        let changed = grid_info.copy_nav(&grid);
+        field.update(&grid_info, &changed);
+        assert_eq!(costs(&field), costs(&FlowField::new(&grid_info, goal)));
+        assert!(field.cost(UVec3::ZERO).is_some());
+    }
+
+    #[test]
+    fn one_way_portal() {
+        // Sides of the wall are only connected by the portal
+        let mut grid = grid();
+        grid.set_nav(UVec3::new(4, 0, 0), Nav::Impassable);
+        grid.set_nav(UVec3::new(4, SIZE - 1, 0), Nav::Impassable);
+        grid.set_nav(
+            UVec3::new(1, 1, 0),
+            Nav::Portal(Portal::to(UVec3::new(6, 6, 0), 1, true)),
+        );
+        grid.build();
+        let mut grid_info = GridInfo::default();
+        grid_info.copy_nav(&grid);
+
+        // The field follows moves toward the goal, which can't go back through the portal
+        for goal in [UVec3::new(1, 1, 0), UVec3::new(6, 6, 0)] {
+            let field = FlowField::new(&grid_info, goal);
+            for tile in (0..SIZE).flat_map(|y| (0..SIZE).map(move |x| UVec3::new(x, y, 0))) {
+                let area = ReachableArea::new(&grid_info, tile, u32::MAX);
+                assert_eq!(field.cost(tile), area.cost(goal), "from {tile} to {goal}");
+            }
+        }
+    }
+}
@@ -0,0 +1,475 @@
+use crate::grid::reachable::ReachableArea;
+use crate::grid::{
+    GridInfo,
+    Grids,
+};
+use crate::linear::LinearMovement;
+use crate::{
+    Destination,
+    LinkTraversed,
+    PathNotFound,
+    TraverseLink,
+};
+use bevy::prelude::{
+    Commands,
+    Component,
+    Entity,
+    On,
+    Query,
+    Res,
+    Resource,
+    Transform,
+    UVec3,
+    With,
+};
+use bevy_northstar::prelude::{
+    AgentOfGrid,
+    AgentPos,
+    NextPos,
+    Path,
+    Pathfind,
+};
+use std::cmp::Reverse;
+use std::collections::{
+    BinaryHeap,
+    VecDeque,
+};
+
+/// Tile on a grid. `grid` is `None` for the `GridInfo` resource.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct GridPos {
+    pub grid: Option<Entity>,
+    pub tile: UVec3,
+}
+
+impl GridPos {
+    pub fn new(grid: Option<Entity>, tile: UVec3) -> Self {
+        Self { grid, tile }
+    }
+}
+
+/// How agent moves through a link
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+pub enum LinkTraversal {
+    /// Move straight to the other end, e.g. stairs or ladders. Use `LinearMovement::speed` if `speed` is `None`.
+    Walk { speed: Option<f32> },
+    /// Appear at the other end immediately
+    #[default]
+    Teleport,
+    /// [`TraverseLink`] is triggered and agent waits until [`LinkTraversed`] is triggered.
+    /// You should move the agent to the other end yourself.
+    Custom,
+}
+
+/// One-way connection between two tiles, on the same or different grids
+#[derive(Clone, Debug)]
+pub struct GridLink {
+    pub from: GridPos,
+    pub to: GridPos,
+    pub cost: u32,
+    pub traversal: LinkTraversal,
+}
+
+/// Links between grid layers, e.g. floors of a building
+#[derive(Resource, Default)]
+pub struct GridLinks {
+    links: Vec<GridLink>,
+}
+
+impl GridLinks {
+    /// Add a link and return its index
+    pub fn add(&mut self, link: GridLink) -> usize {
+        self.links.push(link);
+        self.links.len() - 1
+    }
+
+    /// Add a link and the reversed one
+    pub fn add_two_way(&mut self, link: GridLink) {
+        let reversed = GridLink {
+            from: link.to,
+            to: link.from,
+            ..link.clone()
+        };
+        self.add(link);
+        self.add(reversed);
+    }
+
+    pub fn get(&self, index: usize) -> Option<&GridLink> {
+        self.links.get(index)
+    }
+
+    /// Links to go through, in order, to move between grids.
+    /// Moving on a grid is costed by searching the grid, with `grid_info` of each grid.
+    pub fn route<'a>(
+        &self,
+        grid_info: impl Fn(Option<Entity>) -> &'a GridInfo,
+        from: GridPos,
+        to: GridPos,
+    ) -> Option<Vec<usize>> {
+        // Node `None` is the start, `Some(i)` is the end of link `i`
+        let mut best: Vec<Option<(u32, Option<usize>)>> = vec![None; self.links.len()];
+        let mut heap = BinaryHeap::from([Reverse((0, None::<usize>))]);
+        let mut goal: Option<(u32, Option<usize>)> = None;
+        while let Some(Reverse((cost, node))) = heap.pop() {
+            if goal.is_some_and(|(goal_cost, _)| goal_cost <= cost) {
+                break;
+            }
+            if node.is_some_and(|i| best[i].is_some_and(|(c, _)| c < cost)) {
+                continue;
+            }
+
+            let pos = node.map_or(from, |i| self.links[i].to);
+            let area = ReachableArea::new(grid_info(pos.grid), pos.tile, u32::MAX);
+            if pos.grid == to.grid {
+                if let Some(total) = area.cost(to.tile).map(|leg| cost + leg) {
+                    if goal.is_none_or(|(goal_cost, _)| total < goal_cost) {
+                        goal = Some((total, node));
+                    }
+                }
+            }
+
+            for (i, link) in self.links.iter().enumerate() {
+                if link.from.grid != pos.grid {
+                    continue;
+                }
+                let Some(leg) = area.cost(link.from.tile) else {
+                    continue;
+                };
+                let new_cost = cost + leg + link.cost;
+                if best[i].is_none_or(|(c, _)| new_cost < c) {
+                    best[i] = Some((new_cost, node));
+                    heap.push(Reverse((new_cost, Some(i))));
+                }
+            }
+        }
+
+        let (_, mut node) = goal?;
+        let mut route = Vec::new();
+        while let Some(i) = node {
+            route.push(i);
+            node = best[i].and_then(|(_, previous)| previous);
+        }
+        route.reverse();
+        Some(route)
+    }
+}
+
+/// Move to a tile on another grid through links
+#[derive(Component, Debug)]
+pub struct LinkRoute {
+    links: VecDeque<usize>,
+    goal: UVec3,
+    stage: LinkStage,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum LinkStage {
+    /// Find path to the next link or the goal
+    Start,
+    /// Moving to the next link
+    ToLink,
+    /// Moving through the link
+    Traversing,
+    /// Arrived at the other end of the link
+    Traversed,
+}
+
+impl LinkRoute {
+    /// `links` are indices of [`GridLinks`], `goal` is the tile on the grid at the end of the last link
+    pub fn new(links: Vec<usize>, goal: UVec3) -> Self {
+        Self {
+            links: links.into(),
+            goal,
+            stage: LinkStage::Start,
+        }
+    }
+}
+
+pub(crate) fn follow_link_route(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut LinkRoute, &AgentPos, &mut LinearMovement, &mut Transform)>,
+    links: Res<GridLinks>,
+    grids: Grids,
+) {
+    for (entity, mut route, agent_pos, mut movement, mut transform) in query.iter_mut() {
+        let link = route.links.front().and_then(|i| links.get(*i));
+        match route.stage {
+            LinkStage::Start => {
+                let Some(link) = link else {
+                    commands.entity(entity).remove::<LinkRoute>();
+                    if agent_pos.0 != route.goal {
+                        commands.entity(entity).insert(Pathfind::new(route.goal));
+                    }
+                    continue;
+                };
+                // Northstar fails to find path to the current tile
+                if agent_pos.0 != link.from.tile {
+                    commands.entity(entity).insert(Pathfind::new(link.from.tile));
+                }
+                route.stage = LinkStage::ToLink;
+            }
+            LinkStage::ToLink => {
+                let Some(link) = link else {
+                    route.stage = LinkStage::Start;
+                    continue;
+                };
+                if agent_pos.0 != link.from.tile || !movement.des.is_empty() {
+                    continue;
+                }
+
+                commands.entity(entity).remove::<(Pathfind, NextPos, Path)>();
+                let pos = grids.get(link.to.grid).tile_to_world(link.to.tile);
+                match link.traversal {
+                    LinkTraversal::Walk { speed } => {
+                        movement.des = vec![Destination {
+                            pos,
+                            custom_velocity: speed,
+                        }];
+                        route.stage = LinkStage::Traversing;
+                    }
+                    LinkTraversal::Teleport => {
+                        transform.translation = pos + movement.offset;
+                        route.stage = LinkStage::Traversed;
+                    }
+                    LinkTraversal::Custom => {
+                        commands.trigger(TraverseLink {
+                            entity,
+                            link: route.links[0],
+                        });
+                        route.stage = LinkStage::Traversing;
+                    }
+                }
+            }
+            LinkStage::Traversing => {
+                let is_walking = link.is_some_and(|link| matches!(link.traversal, LinkTraversal::Walk { .. }));
+                if is_walking && movement.des.is_empty() {
+                    route.stage = LinkStage::Traversed;
+                }
+            }
+            LinkStage::Traversed => {
+                if let Some(link) = link {
+                    match link.to.grid {
+                        Some(grid) => commands.entity(entity).insert(AgentOfGrid(grid)),
+                        None => commands.entity(entity).remove::<AgentOfGrid>(),
+                    };
+                    commands.entity(entity).insert(AgentPos(link.to.tile));
+                }
+                route.links.pop_front();
+                route.stage = LinkStage::Start;
+            }
+        }
+    }
+}
+
+pub(crate) fn link_traversed(trigger: On<LinkTraversed>, mut query: Query<&mut LinkRoute>) {
+    if let Ok(mut route) = query.get_mut(trigger.entity) {
+        if route.stage == LinkStage::Traversing {
+            route.stage = LinkStage::Traversed;
+        }
+    }
+}
+
+pub(crate) fn cancel_link_route(trigger: On<PathNotFound>, mut commands: Commands, query: Query<(), With<LinkRoute>>) {
+    if query.contains(trigger.entity) {
+        commands.entity(trigger.entity).remove::<LinkRoute>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy::prelude::{
+        Vec3,
+        World,
+    };
+    use bevy_northstar::prelude::{
+        CardinalGrid,
+        GridSettingsBuilder,
+        Nav,
+    };
+
+    fn link(from: GridPos, to: GridPos, cost: u32) -> GridLink {
+        GridLink {
+            from,
+            to,
+            cost,
+            traversal: LinkTraversal::Teleport,
+        }
+    }
+
+    /// Open 10x10 floor
+    fn floor() -> GridInfo {
+        GridInfo {
+            grid_size: Some(UVec3::new(10, 10, 1)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn route_between_floors() {
+        let mut world = World::new();
+        let upstairs = Some(world.spawn_empty().id());
+        let mut links = GridLinks::default();
+        let far = links.add(link(
+            GridPos::new(None, UVec3::new(9, 0, 0)),
+            GridPos::new(upstairs, UVec3::new(9, 0, 0)),
+            1,
+        ));
+        let near = links.add(link(
+            GridPos::new(None, UVec3::new(2, 0, 0)),
+            GridPos::new(upstairs, UVec3::new(2, 0, 0)),
+            5,
+        ));
+        links.add_two_way(link(
+            GridPos::new(upstairs, UVec3::new(5, 5, 0)),
+            GridPos::new(None, UVec3::new(5, 5, 0)),
+            1,
+        ));
+
+        let floor = floor();
+        let route = |to: GridPos| links.route(|_| &floor, GridPos::new(None, UVec3::ZERO), to);
+        assert_eq!(route(GridPos::new(upstairs, UVec3::new(3, 0, 0))), Some(vec![near]));
+        assert_eq!(route(GridPos::new(upstairs, UVec3::new(9, 1, 0))), Some(vec![far]));
+        assert_eq!(route(GridPos::new(None, UVec3::new(4, 4, 0))), Some(vec![]));
+
+        let basement = Some(world.spawn_empty().id());
+        assert_eq!(route(GridPos::new(basement, UVec3::ZERO)), None);
+    }
+
+    #[test]
+    fn route_around_walls() {
+        let mut world = World::new();
+        let upstairs = Some(world.spawn_empty().id());
+        let mut links = GridLinks::default();
+        let far = links.add(link(
+            GridPos::new(None, UVec3::new(9, 0, 0)),
+            GridPos::new(upstairs, UVec3::new(9, 0, 0)),
+            1,
+        ));
+        links.add(link(
+            GridPos::new(None, UVec3::new(2, 0, 0)),
+            GridPos::new(upstairs, UVec3::new(2, 0, 0)),
+            1,
+        ));
+
+        // The near link is walled in on the ground floor
+        let mut grid = CardinalGrid::new(&GridSettingsBuilder::new_2d(10, 10).chunk_size(5).build());
+        for tile in [UVec3::new(1, 0, 0), UVec3::new(2, 1, 0), UVec3::new(3, 0, 0)] {
+            grid.set_nav(tile, Nav::Impassable);
+        }
+        grid.build();
+        let mut ground = floor();
+        ground.copy_nav(&grid);
+        let floor = floor();
+        let grid_info = |grid: Option<Entity>| if grid.is_none() { &ground } else { &floor };
+
+        let start = GridPos::new(None, UVec3::ZERO);
+        assert_eq!(
+            links.route(grid_info, start, GridPos::new(upstairs, UVec3::new(3, 0, 0))),
+            Some(vec![far])
+        );
+        assert_eq!(
+            links.route(grid_info, start, GridPos::new(None, UVec3::new(2, 0, 0))),
+            None
+        );
+    }
+
+    #[test]
+    fn route_through_floors() {
+        let mut world = World::new();
+        let first = Some(world.spawn_empty().id());
+        let second = Some(world.spawn_empty().id());
+        let mut links = GridLinks::default();
+        let up = links.add(link(
+            GridPos::new(None, UVec3::ZERO),
+            GridPos::new(first, UVec3::ZERO),
+            1,
+        ));
+        let upper = links.add(link(
+            GridPos::new(first, UVec3::new(4, 0, 0)),
+            GridPos::new(second, UVec3::new(4, 0, 0)),
+            1,
+        ));
+
+        let floor = floor();
+        let route = links.route(
+            |_| &floor,
+            GridPos::new(None, UVec3::ZERO),
+            GridPos::new(second, UVec3::ZERO),
+        );
+        assert_eq!(route, Some(vec![up, upper]));
+    }
+
+    fn spawn_on_route(world: &mut World, link: GridLink, agent_tile: UVec3, goal: UVec3) -> Entity {
+        world.insert_resource(GridInfo {
+            tile_size: Vec3::ONE,
+            ..Default::default()
+        });
+        let mut links = GridLinks::default();
+        let index = links.add(link);
+        world.insert_resource(links);
+        world
+            .spawn((
+                LinkRoute::new(vec![index], goal),
+                AgentPos(agent_tile),
+                LinearMovement::default(),
+                Transform::default(),
+            ))
+            .id()
+    }
+
+    fn follow(world: &mut World) {
+        world.run_system_once(follow_link_route).unwrap();
+    }
+
+    #[test]
+    fn follow_route_to_link() {
+        let from = GridPos::new(None, UVec3::new(3, 0, 0));
+        let to = GridPos::new(None, UVec3::new(3, 5, 0));
+        let mut world = World::new();
+        let entity = spawn_on_route(&mut world, link(from, to, 1), UVec3::ZERO, UVec3::new(5, 5, 0));
+
+        follow(&mut world);
+        assert_eq!(
+            world.get::<Pathfind>(entity).map(|pathfind| pathfind.goal),
+            Some(from.tile)
+        );
+        assert_eq!(world.get::<LinkRoute>(entity).unwrap().stage, LinkStage::ToLink);
+
+        // Still walking to the link
+        follow(&mut world);
+        assert_eq!(world.get::<LinkRoute>(entity).unwrap().stage, LinkStage::ToLink);
+    }
+
+    #[test]
+    fn follow_route_from_link() {
+        let mut world = World::new();
+        let upstairs = world.spawn_empty().id();
+        let from = GridPos::new(None, UVec3::new(3, 0, 0));
+        let to = GridPos::new(Some(upstairs), UVec3::new(3, 5, 0));
+        let goal = UVec3::new(5, 5, 0);
+        let entity = spawn_on_route(&mut world, link(from, to, 1), from.tile, goal);
+
+        // Already standing on the link, no path finding to the current tile
+        follow(&mut world);
+        assert!(world.get::<Pathfind>(entity).is_none());
+        assert_eq!(world.get::<LinkRoute>(entity).unwrap().stage, LinkStage::ToLink);
+
+        follow(&mut world);
+        assert_eq!(world.get::<LinkRoute>(entity).unwrap().stage, LinkStage::Traversed);
+        assert_eq!(
+            world.get::<Transform>(entity).unwrap().translation,
+            Vec3::new(3., 5., 0.)
+        );
+
+        follow(&mut world);
+        assert_eq!(world.get::<AgentOfGrid>(entity).map(|grid| grid.0), Some(upstairs));
+        assert_eq!(world.get::<AgentPos>(entity).map(|pos| pos.0), Some(to.tile));
+        assert_eq!(world.get::<LinkRoute>(entity).unwrap().stage, LinkStage::Start);
+
+        follow(&mut world);
+        assert!(world.get::<LinkRoute>(entity).is_none());
+        assert_eq!(world.get::<Pathfind>(entity).map(|pathfind| pathfind.goal), Some(goal));
+    }
+}
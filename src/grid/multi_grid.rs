@@ -0,0 +1,147 @@
+use bevy::platform::collections::HashMap;
+use bevy::prelude::{
+    Changed,
+    Commands,
+    Component,
+    Entity,
+    Query,
+    Res,
+    UVec3,
+    With,
+    Without,
+};
+use bevy_northstar::prelude::{
+    AgentMask,
+    AgentOfGrid,
+    AgentPos,
+    Blocking,
+    Grid,
+    Neighborhood,
+    NextPos,
+    NorthstarPluginSettings,
+    Path,
+    Pathfind,
+    PathfindArgs,
+    PathfindingFailed,
+};
+
+/// Path finding request of an agent with [`AgentOfGrid`], inserted when its `Pathfind` is changed
+#[derive(Component)]
+#[component(storage = "SparseSet")]
+pub struct NeedsGridPathfinding;
+
+pub(crate) fn tag_grid_pathfinding(
+    mut commands: Commands,
+    query: Query<Entity, (Changed<Pathfind>, With<AgentOfGrid>)>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).try_insert(NeedsGridPathfinding);
+    }
+}
+
+/// Find paths on the grid of each agent's [`AgentOfGrid`].
+///
+/// Northstar's systems only run when there is a single grid of the neighborhood.
+/// With more grids, this system and [`next_position_on_grids`] take over for agents with `AgentOfGrid`.
+/// Northstar's collision avoidance and rerouting are not applied, use [`super::reservation::ReserveTiles`] instead.
+///
+/// Both are added to `PathingSet` for Northstar's neighborhoods. Add them for your custom neighborhood.
+#[allow(clippy::type_complexity)]
+pub fn pathfind_on_grids<N: Neighborhood + 'static>(
+    mut commands: Commands,
+    grids: Query<&Grid<N>>,
+    mut query: Query<(Entity, &AgentPos, &Pathfind, &AgentOfGrid, Option<&mut AgentMask>), With<NeedsGridPathfinding>>,
+    blockers: Query<(Entity, &AgentPos, &AgentOfGrid), With<Blocking>>,
+    settings: Option<Res<NorthstarPluginSettings>>,
+) {
+    let is_multi_grid = grids.iter().nth(1).is_some();
+    let settings = settings.map_or_else(NorthstarPluginSettings::default, |settings| *settings);
+    let mut blocking: HashMap<Entity, HashMap<UVec3, Entity>> = HashMap::new();
+    if is_multi_grid {
+        for (entity, agent_pos, agent_of_grid) in blockers.iter() {
+            blocking.entry(agent_of_grid.0).or_default().insert(agent_pos.0, entity);
+        }
+    }
+
+    let mut count = 0;
+    for (entity, start, pathfind, agent_of_grid, mut agent_mask) in query.iter_mut() {
+        let Ok(grid) = grids.get(agent_of_grid.0) else {
+            continue;
+        };
+        // Handled by Northstar
+        if !is_multi_grid {
+            commands.entity(entity).remove::<NeedsGridPathfinding>();
+            continue;
+        }
+        if count >= settings.max_pathfinding_agents_per_frame {
+            break;
+        }
+        count += 1;
+
+        commands.entity(entity).remove::<NeedsGridPathfinding>();
+        if start.0 == pathfind.goal {
+            commands.entity(entity).remove::<Pathfind>();
+            continue;
+        }
+
+        let mut args = PathfindArgs::new(start.0, pathfind.goal)
+            .mode(pathfind.mode.unwrap_or(settings.pathfind_settings.default_mode));
+        if pathfind.limits.partial {
+            args = args.partial();
+        }
+        if let Some(region) = pathfind.limits.boundary {
+            args = args.search_region(region);
+        }
+        if let Some(distance) = pathfind.limits.distance {
+            args = args.max_distance(distance);
+        }
+        if let Some(blocking) = blocking.get(&agent_of_grid.0) {
+            args = args.blocking(blocking);
+        }
+        if let Some(agent_mask) = agent_mask.as_mut() {
+            args = args.mask(&mut agent_mask.0);
+        }
+
+        match grid.pathfind(&mut args) {
+            Some(path) => {
+                commands
+                    .entity(entity)
+                    .try_insert(path)
+                    .try_remove::<PathfindingFailed>();
+            }
+            None => {
+                commands
+                    .entity(entity)
+                    .try_insert(PathfindingFailed)
+                    .try_remove::<NextPos>();
+            }
+        }
+    }
+}
+
+/// Move agents with [`AgentOfGrid`] along their path when there are multiple grids, see [`pathfind_on_grids`]
+#[allow(clippy::type_complexity)]
+pub fn next_position_on_grids<N: Neighborhood + 'static>(
+    mut commands: Commands,
+    grids: Query<(), With<Grid<N>>>,
+    mut query: Query<
+        (Entity, &mut Path, &AgentPos, &Pathfind, &AgentOfGrid),
+        (Without<NextPos>, Without<PathfindingFailed>),
+    >,
+) {
+    if grids.iter().nth(1).is_none() {
+        return;
+    }
+
+    for (entity, mut path, agent_pos, pathfind, agent_of_grid) in query.iter_mut() {
+        if !grids.contains(agent_of_grid.0) {
+            continue;
+        }
+
+        if agent_pos.0 == pathfind.goal {
+            commands.entity(entity).try_remove::<Path>().try_remove::<Pathfind>();
+        } else if let Some(next) = path.pop() {
+            commands.entity(entity).try_insert(NextPos(next));
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use crate::grid::{
+    GridInfo,
+    SEARCH_LIMIT,
+};
+use crate::Destination;
+use bevy::platform::collections::HashMap;
+use bevy::prelude::UVec3;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// Tiles which can be reached from a start tile within a movement budget
+#[derive(Clone, Debug)]
+pub struct ReachableArea {
+    pub start: UVec3,
+    pub budget: u32,
+    /// Total cost and previous tile on the cheapest path of each reachable tile
+    tiles: HashMap<UVec3, (u32, UVec3)>,
+}
+
+impl ReachableArea {
+    pub fn new(grid_info: &GridInfo, start: UVec3, budget: u32) -> Self {
+        let mut tiles = HashMap::new();
+        if grid_info.is_passable(start) {
+            tiles.insert(start, (0, start));
+        }
+
+        let mut heap = BinaryHeap::from([Reverse((0, start.to_array()))]);
+        while let Some(Reverse((cost, tile))) = heap.pop() {
+            let tile = UVec3::from_array(tile);
+            if tiles.get(&tile).is_none_or(|(c, _)| *c != cost) {
+                continue;
+            }
+            if grid_info.grid_size.is_none() && tiles.len() >= SEARCH_LIMIT {
+                continue;
+            }
+
+            for neighbor in grid_info.neighbors(tile) {
+                let Some(step) = grid_info.cost(neighbor) else {
+                    continue;
+                };
+                let new_cost = cost + step;
+                if new_cost <= budget && tiles.get(&neighbor).is_none_or(|(old_cost, _)| new_cost < *old_cost) {
+                    tiles.insert(neighbor, (new_cost, tile));
+                    heap.push(Reverse((new_cost, neighbor.to_array())));
+                }
+            }
+        }
+
+        Self { start, budget, tiles }
+    }
+
+    pub fn contains(&self, tile: UVec3) -> bool {
+        self.tiles.contains_key(&tile)
+    }
+
+    /// Total cost to move from start to tile
+    pub fn cost(&self, tile: UVec3) -> Option<u32> {
+        self.tiles.get(&tile).map(|(cost, _)| *cost)
+    }
+
+    /// Previous tile on the cheapest path to tile
+    pub fn previous(&self, tile: UVec3) -> Option<UVec3> {
+        self.tiles.get(&tile).map(|(_, previous)| *previous)
+    }
+
+    /// Reachable tiles with their cost
+    pub fn iter(&self) -> impl Iterator<Item = (UVec3, u32)> + '_ {
+        self.tiles.iter().map(|(tile, (cost, _))| (*tile, *cost))
+    }
+
+    /// Cheapest path from start to tile, both included
+    pub fn path_to(&self, tile: UVec3) -> Option<Vec<UVec3>> {
+        if !self.contains(tile) {
+            return None;
+        }
+
+        let mut path = vec![tile];
+        let mut current = tile;
+        while current != self.start {
+            current = self.previous(current)?;
+            path.push(current);
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Destinations to move along the path to tile, ready for `LinearMovement::des`
+    pub fn destinations(&self, grid_info: &GridInfo, tile: UVec3, speed: f32) -> Option<Vec<Destination>> {
+        let path = self.path_to(tile)?;
+        Some(
+            path.into_iter()
+                .skip(1)
+                .map(|tile| grid_info.tile_destination(tile, speed))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_northstar::prelude::{
+        CardinalGrid,
+        GridSettingsBuilder,
+        Nav,
+    };
+
+    /// 8x8 grid with a wall at x = 2 from y = 0 to 6 and a mud tile at (1, 3)
+    fn grid_info() -> GridInfo {
+        let mut grid = CardinalGrid::new(&GridSettingsBuilder::new_2d(8, 8).chunk_size(4).build());
+        for y in 0..7 {
+            grid.set_nav(UVec3::new(2, y, 0), Nav::Impassable);
+        }
+        grid.set_nav(UVec3::new(1, 3, 0), Nav::Passable(4));
+        grid.build();
+        let mut grid_info = GridInfo::default();
+        grid_info.copy_nav(&grid);
+        grid_info
+    }
+
+    fn tile(x: u32, y: u32) -> UVec3 {
+        UVec3::new(x, y, 0)
+    }
+
+    #[test]
+    fn budget_cut_off() {
+        let area = ReachableArea::new(&grid_info(), tile(0, 0), 3);
+        assert!(area.contains(tile(0, 3)));
+        assert!(area.contains(tile(1, 2)));
+        assert!(!area.contains(tile(0, 4)));
+        assert!(!area.contains(tile(3, 0)));
+        assert!(area.iter().all(|(_, cost)| cost <= 3));
+    }
+
+    #[test]
+    fn cost_accumulation() {
+        let area = ReachableArea::new(&grid_info(), tile(0, 0), u32::MAX);
+        assert_eq!(area.cost(tile(0, 0)), Some(0));
+        assert_eq!(area.cost(tile(1, 2)), Some(3));
+        // Entering the mud tile costs 4, going around it is cheaper
+        assert_eq!(area.cost(tile(1, 3)), Some(7));
+        assert_eq!(area.cost(tile(1, 4)), Some(5));
+        // Around the wall
+        assert_eq!(area.cost(tile(3, 0)), Some(17));
+        assert_eq!(area.cost(tile(2, 0)), None);
+    }
+
+    #[test]
+    fn path_reconstruction() {
+        let grid_info = grid_info();
+        let area = ReachableArea::new(&grid_info, tile(0, 0), u32::MAX);
+        assert_eq!(area.path_to(tile(0, 0)), Some(vec![tile(0, 0)]));
+
+        let path = area.path_to(tile(1, 4)).unwrap();
+        assert_eq!(path.first(), Some(&tile(0, 0)));
+        assert_eq!(path.last(), Some(&tile(1, 4)));
+        assert!(!path.contains(&tile(1, 3)));
+        let cost: u32 = path[1..].iter().map(|tile| grid_info.cost(*tile).unwrap()).sum();
+        assert_eq!(Some(cost), area.cost(tile(1, 4)));
+        assert!(path
+            .windows(2)
+            .all(|pair| grid_info.neighbors(pair[0]).any(|tile| tile == pair[1])));
+
+        assert_eq!(area.path_to(tile(2, 0)), None);
+    }
+
+    #[test]
+    fn impassable_start() {
+        let area = ReachableArea::new(&grid_info(), tile(2, 0), u32::MAX);
+        assert!(!area.contains(tile(2, 0)));
+        assert_eq!(area.iter().count(), 0);
+        assert_eq!(area.path_to(tile(2, 0)), None);
+        assert_eq!(area.cost(tile(2, 0)), None);
+    }
+}
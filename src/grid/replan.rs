@@ -0,0 +1,191 @@
+use crate::grid::flow_field::FlowFieldGoal;
+use crate::grid::Grids;
+use crate::linear::LinearMovement;
+use crate::{
+    NavChanged,
+    PathReplanned,
+};
+use bevy::platform::collections::HashSet;
+use bevy::prelude::{
+    Commands,
+    Entity,
+    Has,
+    On,
+    Or,
+    Query,
+    ResMut,
+    Resource,
+    UVec3,
+    With,
+};
+use bevy_northstar::prelude::{
+    NextPos,
+    Path,
+    Pathfind,
+};
+use std::collections::VecDeque;
+
+/// Replan agents when nav data of tiles on their path is changed, e.g. by `Grid::set_nav`.
+///
+/// Agents moving by [`Pathfind`] get a new path from Northstar when a tile on the path becomes impassable or its cost is changed.
+/// Agents following [`FlowFieldGoal`] only drop their next tile if it is blocked because the flow field is updated itself.
+#[derive(Resource)]
+pub struct Replanning {
+    /// Maximum number of agents replanned in one frame. The rest are replanned in next frames.
+    pub max_per_frame: usize,
+    pending: VecDeque<Entity>,
+}
+
+impl Default for Replanning {
+    fn default() -> Self {
+        Self {
+            max_per_frame: 32,
+            pending: VecDeque::new(),
+        }
+    }
+}
+
+/// Queue agents whose path goes through changed tiles
+#[allow(clippy::type_complexity)]
+pub(crate) fn queue_replan_on_nav_change(
+    trigger: On<NavChanged>,
+    mut replanning: ResMut<Replanning>,
+    query: Query<
+        (Entity, &LinearMovement, Option<&Path>, Option<&NextPos>, Has<Pathfind>),
+        Or<(With<Pathfind>, With<FlowFieldGoal>)>,
+    >,
+    grids: Grids,
+) {
+    let changed: HashSet<UVec3> = trigger.tiles.iter().copied().collect();
+    for (entity, movement, path, next_pos, is_pathfinding) in query.iter() {
+        let grid = grids.grid_of(entity);
+        if grids.info_owner(grid) != trigger.grid {
+            continue;
+        }
+
+        let grid_info = grids.get(grid);
+        let path_tiles = path.into_iter().flat_map(|path| path.path().iter().copied());
+        let des_tiles = movement.des.iter().filter_map(|des| grid_info.world_to_tile(des.pos));
+        let mut tiles = path_tiles.chain(next_pos.map(|next_pos| next_pos.0)).chain(des_tiles);
+        let needs_replan =
+            tiles.any(|tile| changed.contains(&tile) && (is_pathfinding || !grid_info.is_passable(tile)));
+        if needs_replan && !replanning.pending.contains(&entity) {
+            replanning.pending.push_back(entity);
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn replan_on_nav_change(
+    mut commands: Commands,
+    mut replanning: ResMut<Replanning>,
+    mut query: Query<(&mut LinearMovement, Option<&Pathfind>), Or<(With<Pathfind>, With<FlowFieldGoal>)>>,
+    grids: Grids,
+) {
+    for _ in 0..replanning.max_per_frame {
+        let Some(entity) = replanning.pending.pop_front() else {
+            break;
+        };
+        let Ok((mut movement, pathfind)) = query.get_mut(entity) else {
+            continue;
+        };
+
+        // Stop before walking into a blocked tile
+        let grid_info = grids.of_agent(entity);
+        let next_blocked = movement
+            .des
+            .first()
+            .and_then(|des| grid_info.world_to_tile(des.pos))
+            .is_some_and(|tile| !grid_info.is_passable(tile));
+        if next_blocked {
+            movement.des.clear();
+            commands.entity(entity).remove::<NextPos>();
+        }
+
+        if let Some(pathfind) = pathfind {
+            commands.entity(entity).remove::<Path>().insert(pathfind.clone());
+        }
+        commands.trigger(PathReplanned { entity });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::GridInfo;
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy::prelude::World;
+
+    fn world(max_per_frame: usize) -> World {
+        let mut world = World::new();
+        world.insert_resource(GridInfo::default());
+        world.insert_resource(Replanning {
+            max_per_frame,
+            ..Default::default()
+        });
+        world.add_observer(queue_replan_on_nav_change);
+        world
+    }
+
+    /// Agent moving along `path` to its last tile
+    fn spawn(world: &mut World, path: &[UVec3]) -> Entity {
+        world
+            .spawn((
+                LinearMovement::default(),
+                Pathfind::new(*path.last().unwrap()),
+                Path::new(path.to_vec(), path.len() as u32),
+            ))
+            .id()
+    }
+
+    fn row(y: u32) -> Vec<UVec3> {
+        (0..4).map(|x| UVec3::new(x, y, 0)).collect()
+    }
+
+    fn replanned(world: &World, agents: &[Entity]) -> usize {
+        agents
+            .iter()
+            .filter(|agent| world.get::<Path>(**agent).is_none())
+            .count()
+    }
+
+    #[test]
+    fn queue_affected_agents() {
+        let mut world = world(32);
+        let affected = [spawn(&mut world, &row(0)), spawn(&mut world, &row(0))];
+        let unaffected = [spawn(&mut world, &row(1)), spawn(&mut world, &row(2))];
+
+        world.trigger(NavChanged {
+            grid: None,
+            tiles: vec![UVec3::new(2, 0, 0)],
+        });
+        assert_eq!(world.resource::<Replanning>().pending, affected);
+
+        world.run_system_once(replan_on_nav_change).unwrap();
+        assert_eq!(replanned(&world, &affected), 2);
+        assert_eq!(replanned(&world, &unaffected), 0);
+    }
+
+    #[test]
+    fn limit_per_frame() {
+        let mut world = world(2);
+        let agents: Vec<Entity> = (0..5).map(|_| spawn(&mut world, &row(0))).collect();
+
+        world.trigger(NavChanged {
+            grid: None,
+            tiles: vec![UVec3::new(1, 0, 0)],
+        });
+        // Changing the same tile again doesn't queue agents twice
+        world.trigger(NavChanged {
+            grid: None,
+            tiles: vec![UVec3::new(1, 0, 0)],
+        });
+        assert_eq!(world.resource::<Replanning>().pending.len(), 5);
+
+        for expected in [2, 4, 5] {
+            world.run_system_once(replan_on_nav_change).unwrap();
+            assert_eq!(replanned(&world, &agents), expected);
+        }
+        assert!(world.resource::<Replanning>().pending.is_empty());
+    }
+}
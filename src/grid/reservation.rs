@@ -0,0 +1,411 @@
+use crate::grid::flow_field::FlowFieldGoal;
+use crate::grid::Grids;
+use crate::linear::LinearMovement;
+use bevy::platform::collections::HashMap;
+use bevy::prelude::{
+    Commands,
+    Component,
+    Entity,
+    Query,
+    Res,
+    ResMut,
+    Resource,
+    Time,
+    UVec3,
+};
+use bevy_northstar::prelude::{
+    AgentPos,
+    NextPos,
+    Path,
+    Pathfind,
+};
+
+/// Reserve tiles on the grid so that agents with this component don't step on the same tile.
+/// Agent reserves its current tile, and the next tile before stepping on it.
+///
+/// Agents waiting for each other, e.g. swapping tiles head-on in a corridor, would wait forever.
+/// Then the one with the greatest `Entity` steps aside regardless of `on_conflict`.
+#[derive(Component, Clone, Debug)]
+pub struct ReserveTiles {
+    /// What to do when the next tile is reserved by another agent
+    pub on_conflict: ReservationConflict,
+    /// Seconds waiting for the next tile before rerouting, only for [`ReservationConflict::Reroute`]
+    pub reroute_after: f32,
+    waited: f32,
+    is_waiting: bool,
+    is_sidestepping: bool,
+}
+
+impl Default for ReserveTiles {
+    fn default() -> Self {
+        Self {
+            on_conflict: ReservationConflict::default(),
+            reroute_after: 1.,
+            waited: 0.,
+            is_waiting: false,
+            is_sidestepping: false,
+        }
+    }
+}
+
+impl ReserveTiles {
+    /// Agent is waiting for the next tile to be free. Movement is paused without freezing [`LinearMovement`].
+    pub fn is_waiting(&self) -> bool {
+        self.is_waiting
+    }
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ReservationConflict {
+    /// Wait until the next tile is free
+    #[default]
+    Wait,
+    /// Step aside to a free tile closer to the goal, then find path again
+    Reroute,
+}
+
+struct Reservation {
+    entity: Entity,
+    from: f64,
+    until: f64,
+}
+
+/// Reservation table of tiles, by grid entity and tile
+#[derive(Resource, Default)]
+pub struct Reservations {
+    /// Also reserve tiles on the path ahead which will be reached within this many seconds.
+    /// Reservation then only lasts while the agent is expected to be on the tile.
+    /// If `None`, only current and next tiles are reserved, until the agent leaves them.
+    pub time_window: Option<f32>,
+    tiles: HashMap<(Option<Entity>, UVec3), Vec<Reservation>>,
+    held: HashMap<Entity, Vec<(Option<Entity>, UVec3)>>,
+    /// Agent holding the next tile of each waiting agent
+    waiting_for: HashMap<Entity, Entity>,
+}
+
+impl Reservations {
+    /// Agent which reserved the tile at the time
+    pub fn reserved_by(&self, grid: Option<Entity>, tile: UVec3, time: f64) -> Option<Entity> {
+        self.tiles
+            .get(&(grid, tile))?
+            .iter()
+            .find(|reservation| reservation.from <= time && time < reservation.until)
+            .map(|reservation| reservation.entity)
+    }
+
+    /// Other agent whose reservation of the tile overlaps the time range
+    fn holder(&self, key: (Option<Entity>, UVec3), entity: Entity, from: f64, until: f64) -> Option<Entity> {
+        self.tiles
+            .get(&key)?
+            .iter()
+            .find(|r| r.entity != entity && from < r.until && r.from < until)
+            .map(|r| r.entity)
+    }
+
+    fn is_free(&self, key: (Option<Entity>, UVec3), entity: Entity, from: f64, until: f64) -> bool {
+        self.holder(key, entity, from, until).is_none()
+    }
+
+    /// Agents in the cycle if waiting for `holder` leads back to `entity`
+    fn wait_cycle(&self, entity: Entity, holder: Entity) -> Option<Vec<Entity>> {
+        let mut cycle = vec![entity];
+        let mut current = holder;
+        while current != entity {
+            if cycle.contains(&current) {
+                return None;
+            }
+            cycle.push(current);
+            current = *self.waiting_for.get(&current)?;
+        }
+        Some(cycle)
+    }
+
+    fn reserve(&mut self, key: (Option<Entity>, UVec3), entity: Entity, from: f64, until: f64) {
+        self.tiles
+            .entry(key)
+            .or_default()
+            .push(Reservation { entity, from, until });
+        self.held.entry(entity).or_default().push(key);
+    }
+
+    fn release(&mut self, entity: Entity) {
+        for key in self.held.remove(&entity).unwrap_or_default() {
+            if let Some(reservations) = self.tiles.get_mut(&key) {
+                reservations.retain(|reservation| reservation.entity != entity);
+                if reservations.is_empty() {
+                    self.tiles.remove(&key);
+                }
+            }
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn reserve_tiles(
+    mut commands: Commands,
+    mut reservations: ResMut<Reservations>,
+    mut query: Query<(
+        Entity,
+        &AgentPos,
+        &mut LinearMovement,
+        &mut ReserveTiles,
+        Option<&Pathfind>,
+        Option<&Path>,
+        Option<&FlowFieldGoal>,
+    )>,
+    grids: Grids,
+    time: Res<Time>,
+) {
+    let gone: Vec<Entity> = reservations
+        .held
+        .keys()
+        .filter(|e| !query.contains(**e))
+        .copied()
+        .collect();
+    for entity in gone {
+        reservations.release(entity);
+        reservations.waiting_for.remove(&entity);
+    }
+
+    let now = time.elapsed_secs_f64();
+    let window = reservations.time_window.map(f64::from);
+    let until = |time: f64| if window.is_some() { time } else { f64::INFINITY };
+
+    for (entity, agent_pos, mut movement, mut reserve, pathfind, path, flow_goal) in query.iter_mut() {
+        reservations.release(entity);
+        reservations.waiting_for.remove(&entity);
+        let grid = grids.grid_of(entity);
+        let grid_info = grids.get(grid);
+        let current = agent_pos.0;
+        let next = movement
+            .des
+            .first()
+            .and_then(|des| grid_info.world_to_tile(des.pos))
+            .filter(|tile| *tile != current);
+
+        // Frozen by user, or not moving
+        let Some(next) = next.filter(|_| !movement.is_freezed) else {
+            reservations.reserve((grid, current), entity, now, f64::INFINITY);
+            if reserve.is_waiting {
+                reserve.is_waiting = false;
+                reserve.waited = 0.;
+            }
+            if reserve.is_sidestepping && movement.des.is_empty() {
+                reserve.is_sidestepping = false;
+                if let Some(pathfind) = pathfind {
+                    commands.entity(entity).insert(pathfind.clone());
+                }
+            }
+            continue;
+        };
+
+        // Seconds to move one tile
+        let speed = movement.des[0].custom_velocity.unwrap_or(movement.speed);
+        let step = (grid_info.tile_to_world(current).distance(grid_info.tile_to_world(next)) / speed) as f64;
+
+        if reservations.is_free((grid, next), entity, now, until(now + 2. * step)) {
+            if reserve.is_waiting {
+                reserve.is_waiting = false;
+                reserve.waited = 0.;
+            }
+            reservations.reserve((grid, current), entity, now, until(now + step));
+            reservations.reserve((grid, next), entity, now, until(now + 2. * step));
+
+            if let (Some(window), Some(path)) = (window, path) {
+                for (i, tile) in path.path().iter().enumerate() {
+                    let from = now + (i + 1) as f64 * step;
+                    if from > now + window || !reservations.is_free((grid, *tile), entity, from, from + 2. * step) {
+                        break;
+                    }
+                    reservations.reserve((grid, *tile), entity, from, from + 2. * step);
+                }
+            }
+            continue;
+        }
+
+        let holder = reservations.holder((grid, next), entity, now, until(now + 2. * step));
+        reservations.reserve((grid, current), entity, now, f64::INFINITY);
+        reserve.is_waiting = true;
+        reserve.waited += time.delta_secs();
+        let is_deadlocked = holder
+            .and_then(|holder| reservations.wait_cycle(entity, holder))
+            .is_some_and(|cycle| cycle.iter().all(|other| *other <= entity));
+        if let Some(holder) = holder {
+            reservations.waiting_for.insert(entity, holder);
+        }
+        let is_rerouting =
+            reserve.on_conflict == ReservationConflict::Reroute && reserve.waited >= reserve.reroute_after;
+        if !is_deadlocked && !is_rerouting {
+            continue;
+        }
+
+        // Step aside to the free tile nearest to the goal
+        let goal = pathfind.map(|pathfind| pathfind.goal).or(flow_goal.map(|goal| goal.0));
+        let distance = |tile: &UVec3| goal.map_or(0., |goal| tile.as_vec3().distance_squared(goal.as_vec3()));
+        let side = grid_info
+            .neighbors(current)
+            .filter(|tile| *tile != next && grid_info.is_passable(*tile))
+            .filter(|tile| reservations.is_free((grid, *tile), entity, now, until(now + 2. * step)))
+            .min_by(|a, b| distance(a).total_cmp(&distance(b)));
+        if let Some(side) = side {
+            reservations.waiting_for.remove(&entity);
+            reservations.reserve((grid, side), entity, now, until(now + 2. * step));
+            movement.des = vec![grid_info.tile_destination(side, movement.speed)];
+            commands.entity(entity).remove::<(NextPos, Path)>();
+            reserve.is_waiting = false;
+            reserve.waited = 0.;
+            reserve.is_sidestepping = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::GridInfo;
+    use crate::Destination;
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy::prelude::{
+        Vec3,
+        World,
+    };
+    use bevy_northstar::prelude::{
+        CardinalGrid,
+        GridSettingsBuilder,
+        Nav,
+    };
+    use std::time::Duration;
+
+    /// 8x8 grid, walls at `blocked`
+    fn world(blocked: &[UVec3]) -> World {
+        let mut grid = CardinalGrid::new(&GridSettingsBuilder::new_2d(8, 8).chunk_size(4).build());
+        for tile in blocked {
+            grid.set_nav(*tile, Nav::Impassable);
+        }
+        grid.build();
+        let mut grid_info = GridInfo {
+            tile_size: Vec3::ONE,
+            ..Default::default()
+        };
+        grid_info.copy_nav(&grid);
+
+        let mut world = World::new();
+        world.insert_resource(grid_info);
+        world.init_resource::<Reservations>();
+        world.init_resource::<Time>();
+        world
+    }
+
+    fn spawn(world: &mut World, tile: UVec3, next: Option<UVec3>, on_conflict: ReservationConflict) -> Entity {
+        world
+            .spawn((
+                AgentPos(tile),
+                LinearMovement {
+                    speed: 1.,
+                    des: next
+                        .map(|next| vec![Destination::from_pos(next.as_vec3())])
+                        .unwrap_or_default(),
+                    ..Default::default()
+                },
+                ReserveTiles {
+                    on_conflict,
+                    ..Default::default()
+                },
+            ))
+            .id()
+    }
+
+    fn reserve(world: &mut World, seconds: f32) {
+        world
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(seconds));
+        world.run_system_once(reserve_tiles).unwrap();
+    }
+
+    fn reserved_by(world: &World, tile: UVec3) -> Option<Entity> {
+        let now = world.resource::<Time>().elapsed_secs_f64();
+        world.resource::<Reservations>().reserved_by(None, tile, now)
+    }
+
+    fn is_waiting(world: &World, entity: Entity) -> bool {
+        world.get::<ReserveTiles>(entity).unwrap().is_waiting()
+    }
+
+    fn next_tile(world: &World, entity: Entity) -> Option<UVec3> {
+        let movement = world.get::<LinearMovement>(entity).unwrap();
+        movement.des.first().map(|des| des.pos.as_uvec3())
+    }
+
+    fn tile(x: u32, y: u32) -> UVec3 {
+        UVec3::new(x, y, 0)
+    }
+
+    #[test]
+    fn reserve_current_and_next_tiles() {
+        let mut world = world(&[]);
+        let first = spawn(&mut world, tile(0, 0), Some(tile(1, 0)), ReservationConflict::Wait);
+        let second = spawn(&mut world, tile(2, 0), Some(tile(1, 0)), ReservationConflict::Wait);
+        reserve(&mut world, 0.1);
+
+        assert_eq!(reserved_by(&world, tile(0, 0)), Some(first));
+        assert_eq!(reserved_by(&world, tile(1, 0)), Some(first));
+        assert_eq!(reserved_by(&world, tile(2, 0)), Some(second));
+        assert!(!is_waiting(&world, first));
+        assert!(is_waiting(&world, second));
+    }
+
+    #[test]
+    fn release_despawned_agent() {
+        let mut world = world(&[]);
+        let first = spawn(&mut world, tile(0, 0), Some(tile(1, 0)), ReservationConflict::Wait);
+        let second = spawn(&mut world, tile(2, 0), Some(tile(1, 0)), ReservationConflict::Wait);
+        reserve(&mut world, 0.1);
+
+        world.despawn(first);
+        reserve(&mut world, 0.1);
+        assert_eq!(reserved_by(&world, tile(0, 0)), None);
+        assert_eq!(reserved_by(&world, tile(1, 0)), Some(second));
+        assert!(!is_waiting(&world, second));
+    }
+
+    #[test]
+    fn reroute_after_waiting() {
+        let mut world = world(&[]);
+        spawn(&mut world, tile(1, 1), None, ReservationConflict::Wait);
+        let waiting = spawn(&mut world, tile(2, 1), Some(tile(1, 1)), ReservationConflict::Wait);
+        let rerouting = spawn(&mut world, tile(1, 2), Some(tile(1, 1)), ReservationConflict::Reroute);
+
+        reserve(&mut world, 0.6);
+        assert!(is_waiting(&world, waiting));
+        assert!(is_waiting(&world, rerouting));
+        assert_eq!(next_tile(&world, rerouting), Some(tile(1, 1)));
+
+        // Waited longer than `reroute_after`
+        reserve(&mut world, 0.6);
+        assert!(is_waiting(&world, waiting));
+        assert_eq!(next_tile(&world, waiting), Some(tile(1, 1)));
+        assert!(!is_waiting(&world, rerouting));
+        let side = next_tile(&world, rerouting).unwrap();
+        assert_ne!(side, tile(1, 1));
+        assert_eq!(reserved_by(&world, side), Some(rerouting));
+    }
+
+    #[test]
+    fn swap_in_corridor() {
+        // Corridor along y = 1
+        let walls: Vec<UVec3> = (0..8).flat_map(|x| [tile(x, 0), tile(x, 2)]).collect();
+        let mut world = world(&walls);
+        let first = spawn(&mut world, tile(3, 1), Some(tile(4, 1)), ReservationConflict::Wait);
+        let second = spawn(&mut world, tile(4, 1), Some(tile(3, 1)), ReservationConflict::Wait);
+
+        reserve(&mut world, 0.1);
+        reserve(&mut world, 0.1);
+        // The greater entity backs off to let the other pass
+        let (yielding, back) = if first > second { (first, tile(2, 1)) } else { (second, tile(5, 1)) };
+        let waiting = if yielding == first { second } else { first };
+        assert!(is_waiting(&world, waiting));
+        assert!(!is_waiting(&world, yielding));
+        assert_eq!(next_tile(&world, yielding), Some(back));
+        assert_eq!(reserved_by(&world, back), Some(yielding));
+    }
+}
@@ -1,3 +1,9 @@
+#[cfg(feature = "path_finding")]
+use crate::grid::flow_field::FlowFieldGoal;
+#[cfg(feature = "path_finding")]
+use crate::grid::link::LinkRoute;
+#[cfg(feature = "path_finding")]
+use crate::grid::Grids;
 use crate::linear::LinearMovement;
 use crate::{
     Destination,
@@ -13,6 +19,7 @@ use bevy::prelude::{
     Commands,
     Component,
     Entity,
+    GlobalTransform,
     IntoScheduleConfigs,
     Plugin,
     Query,
@@ -32,6 +39,15 @@ use leafwing_input_manager::prelude::{
     VirtualDPad,
 };
 use leafwing_input_manager::Actionlike;
+#[cfg(feature = "path_finding")]
+use bevy::math::IVec3;
+#[cfg(feature = "path_finding")]
+use bevy_northstar::prelude::{
+    AgentPos,
+    NextPos,
+    Path,
+    Pathfind,
+};
 
 pub(crate) struct KbControlMovementPlugin<T>
 where
@@ -84,6 +100,13 @@ pub struct KbMovementObject {
     pub gamepad: Vec<GamepadStick>,
     /// DPad button to control movement. Default `VirtualDPad::wasd()` & `VirtualDPad::arrow_keys()`.
     pub dpad: Vec<VirtualDPad>,
+    /// Move relative to the yaw of this entity, e.g. the camera, instead of fixed world axes.
+    /// Up input moves forward of the entity projected onto the movement plane.
+    pub relative_to: Option<Entity>,
+    /// Step tile by tile on the grid instead of moving freely.
+    /// Impassable tiles are not entered, and the current step is finished when input is released.
+    #[cfg(feature = "path_finding")]
+    pub grid_step: bool,
 }
 
 impl Default for KbMovementObject {
@@ -92,6 +115,9 @@ impl Default for KbMovementObject {
             is_moving: false,
             gamepad: vec![GamepadStick::LEFT],
             dpad: vec![VirtualDPad::wasd(), VirtualDPad::arrow_keys()],
+            relative_to: None,
+            #[cfg(feature = "path_finding")]
+            grid_step: false,
         }
     }
 }
@@ -121,12 +147,55 @@ fn moving(
         &mut KbMovementObject,
         Entity,
     )>,
+    references: Query<&GlobalTransform>,
     time: Res<Time>,
+    #[cfg(feature = "path_finding")] grids: Grids,
+    #[cfg(feature = "path_finding")] mut agent_positions: Query<&mut AgentPos>,
 ) {
     for (state, mut movement, transform, mut kb_control, entity) in query.iter_mut() {
         if state.axis_pair(&MovementAction::Walk) != Vec2::ZERO {
             kb_control.is_moving = true;
-            let direction = state.clamped_axis_pair(&MovementAction::Walk);
+            let mut direction = state.clamped_axis_pair(&MovementAction::Walk);
+            if let Some(reference) = kb_control
+                .relative_to
+                .and_then(|reference| references.get(reference).ok())
+            {
+                direction = relative_direction(direction, reference);
+            }
+
+            // Keyboard takes over from path finding
+            #[cfg(feature = "path_finding")]
+            {
+                commands
+                    .entity(entity)
+                    .remove::<(Pathfind, NextPos, Path, FlowFieldGoal, LinkRoute)>();
+
+                if kb_control.grid_step {
+                    if movement.des.is_empty() {
+                        let grid_info = grids.of_agent(entity);
+                        let next_tile = grid_info
+                            .world_to_tile(transform.translation - movement.offset)
+                            .and_then(|tile| tile.checked_add_signed(grid_step(direction)))
+                            .filter(|tile| grid_info.is_passable(*tile));
+                        if let Some(tile) = next_tile {
+                            commands.trigger(NextDes {
+                                entity,
+                                des: grid_info.tile_destination(tile, movement.speed),
+                                is_chain: false,
+                            });
+                        }
+                    }
+                    continue;
+                }
+
+                sync_agent_pos(
+                    &mut agent_positions,
+                    &grids,
+                    entity,
+                    transform.translation - movement.offset,
+                );
+            }
+
             // Make a distance litter further than what object can travel in 1 tick
             let distance = movement.speed * time.delta_secs() * 2.;
 
@@ -154,8 +223,71 @@ fn moving(
                 is_chain: false,
             });
         } else if kb_control.is_moving {
+            #[cfg(feature = "path_finding")]
+            if kb_control.grid_step {
+                kb_control.is_moving = false;
+                continue;
+            }
+
+            #[cfg(feature = "path_finding")]
+            sync_agent_pos(
+                &mut agent_positions,
+                &grids,
+                entity,
+                transform.translation - movement.offset,
+            );
+
             movement.stop();
             kb_control.is_moving = false;
         }
     }
 }
+
+/// Moving freely never arrives at a tile, so the tile of agent is updated from its position
+/// for path finding to start from where it stops.
+#[cfg(feature = "path_finding")]
+fn sync_agent_pos(agent_positions: &mut Query<&mut AgentPos>, grids: &Grids, entity: Entity, pos: Vec3) {
+    let Ok(mut agent_pos) = agent_positions.get_mut(entity) else {
+        return;
+    };
+    if let Some(tile) = grids.of_agent(entity).world_to_tile(pos) {
+        if agent_pos.0 != tile {
+            agent_pos.0 = tile;
+        }
+    }
+}
+
+/// Rotate input direction by the yaw of the reference. The result is still in input axes.
+fn relative_direction(direction: Vec2, reference: &GlobalTransform) -> Vec2 {
+    let forward = if cfg!(feature = "2d") {
+        reference.up().truncate()
+    } else {
+        // Camera looking straight down has its up on the ground
+        let forward = reference.forward();
+        let forward = if forward.y.abs() < 0.999 { forward } else { reference.up() };
+        // Up input is -Z
+        Vec2::new(forward.x, -forward.z)
+    };
+    let Some(forward) = forward.try_normalize() else {
+        return direction;
+    };
+    let right = Vec2::new(forward.y, -forward.x);
+
+    right * direction.x + forward * direction.y
+}
+
+/// Tile step along the main axis of input direction
+#[cfg(feature = "path_finding")]
+fn grid_step(direction: Vec2) -> IVec3 {
+    let (x, y) = if direction.x.abs() >= direction.y.abs() {
+        (direction.x.signum() as i32, 0)
+    } else {
+        (0, direction.y.signum() as i32)
+    };
+
+    if cfg!(feature = "2d") {
+        IVec3::new(x, y, 0)
+    } else {
+        IVec3::new(x, 0, -y)
+    }
+}
@@ -1,3 +1,5 @@
+#[cfg(feature = "path_finding")]
+pub mod grid;
 #[cfg(feature = "kb_control")]
 pub mod kb_control;
 pub mod linear;
@@ -8,11 +10,14 @@ use crate::linear::LinearMovementPlugin;
 use bevy::prelude::{
     App,
     Entity,
+    Event,
     EntityEvent,
     Plugin,
     States,
     Vec3,
 };
+#[cfg(feature = "path_finding")]
+use bevy::prelude::UVec3;
 
 /// The main plugin
 #[derive(Default)]
@@ -71,6 +76,77 @@ pub struct Arrived {
     pub pos: Vec3,
 }
 
+/// Triggered when a destination is rejected, e.g. the clicked position is out of the grid
+#[derive(EntityEvent)]
+pub struct DestinationRejected {
+    pub entity: Entity,
+    pub pos: Vec3,
+}
+
+/// Triggered when there is no path to the destination
+#[cfg(feature = "path_finding")]
+#[derive(EntityEvent)]
+pub struct PathNotFound {
+    pub entity: Entity,
+    pub pos: Vec3,
+    /// Position entity is moving to instead, decided by [`grid::PathFallback`]
+    pub fallback: Option<Vec3>,
+}
+
+/// Triggered when nav data of a grid is copied from Northstar and some tiles are changed
+#[cfg(feature = "path_finding")]
+#[derive(Event)]
+pub struct NavChanged {
+    /// Grid entity with the changed [`grid::GridInfo`], `None` for the `GridInfo` resource
+    pub grid: Option<Entity>,
+    /// Tiles whose cost or passability is changed
+    pub tiles: Vec<UVec3>,
+}
+
+/// Triggered when path of entity is replanned because the grid is changed
+#[cfg(feature = "path_finding")]
+#[derive(EntityEvent)]
+pub struct PathReplanned {
+    pub entity: Entity,
+}
+
+/// Triggered when entity starts moving through a link with [`grid::link::LinkTraversal::Custom`]
+#[cfg(feature = "path_finding")]
+#[derive(EntityEvent)]
+pub struct TraverseLink {
+    pub entity: Entity,
+    /// Index of link in [`grid::link::GridLinks`]
+    pub link: usize,
+}
+
+/// Trigger this when entity finishes moving through a link with [`grid::link::LinkTraversal::Custom`]
+#[cfg(feature = "path_finding")]
+#[derive(EntityEvent)]
+pub struct LinkTraversed {
+    pub entity: Entity,
+}
+
+/// Triggered when entity ordered by [`mouse_control::interaction::InteractionOrder`] is in range of target
+#[cfg(feature = "mouse_control")]
+#[derive(EntityEvent)]
+pub struct InteractionReached {
+    pub entity: Entity,
+    pub target: Entity,
+}
+
+/// Triggered when entity with [`linear::turn_based::TurnBasedMovement`] takes a step
+#[derive(EntityEvent)]
+pub struct StepTaken {
+    pub entity: Entity,
+    pub pos: Vec3,
+    /// Budget left in current turn
+    pub remaining: u32,
+}
+
+/// Trigger this to refill budget of all [`linear::turn_based::TurnBasedMovement`]
+#[derive(Event)]
+pub struct AdvanceTurn;
+
 /// Trigger this to set the next destination for entity
 #[derive(EntityEvent)]
 pub struct NextDes {
@@ -1,6 +1,65 @@
 pub mod circle;
+pub mod turn_based;
 
+#[cfg(feature = "path_finding")]
+use crate::grid::flow_field::{
+    flow_field_travel,
+    update_flow_fields,
+    update_flow_fields_on_nav_change,
+    FlowFields,
+};
+#[cfg(feature = "path_finding")]
+use crate::grid::reservation::{
+    reserve_tiles,
+    ReserveTiles,
+    Reservations,
+};
+#[cfg(feature = "path_finding")]
+use crate::grid::link::{
+    cancel_link_route,
+    follow_link_route,
+    link_traversed,
+    GridLinks,
+};
+#[cfg(feature = "path_finding")]
+use crate::grid::multi_grid::{
+    next_position_on_grids,
+    pathfind_on_grids,
+    tag_grid_pathfinding,
+};
+#[cfg(feature = "path_finding")]
+use crate::grid::replan::{
+    queue_replan_on_nav_change,
+    replan_on_nav_change,
+    Replanning,
+};
+#[cfg(feature = "path_finding")]
+use crate::grid::{
+    pathfinding_failed,
+    smooth_northstar_path,
+    sync_grid_nav,
+    Grids,
+};
+#[cfg(feature = "path_finding")]
+pub use crate::grid::GridInfo;
+#[cfg(feature = "path_finding")]
+use bevy::prelude::PreUpdate;
+#[cfg(feature = "path_finding")]
+use bevy_northstar::prelude::{
+    CardinalIsoNeighborhood,
+    CardinalNeighborhood,
+    CardinalNeighborhood3d,
+    OrdinalNeighborhood,
+    OrdinalNeighborhood3d,
+    PathingSet,
+};
 use crate::linear::circle::circle_travel;
+use crate::linear::turn_based::{
+    advance_turn,
+    limit_turn_budget,
+    take_step,
+    TurnBasedMovement,
+};
 use crate::{
     Arrived,
     Destination,
@@ -23,8 +82,7 @@ use avian3d::{
     },
 };
 use bevy::app::App;
-#[cfg(feature = "path_finding")]
-use bevy::prelude::Resource;
+use bevy::ecs::query::QueryData;
 use bevy::prelude::{
     in_state,
     Commands,
@@ -75,20 +133,87 @@ where
         }
 
         #[cfg(not(feature = "path_finding"))]
-        let systems = (circle_travel, check_arrived, straight_travel);
+        let systems = || {
+            (
+                circle_travel,
+                (check_arrived, limit_turn_budget, straight_travel).chain(),
+            )
+        };
+        // Next tile is decided and reserved before travelling
         #[cfg(feature = "path_finding")]
-        let systems = (circle_travel, check_arrived, straight_travel, update_travel_stop);
+        let systems = || {
+            (
+                circle_travel,
+                (
+                    check_arrived,
+                    (
+                        update_travel_stop,
+                        (update_flow_fields, flow_field_travel).chain(),
+                        replan_on_nav_change,
+                        follow_link_route,
+                    ),
+                    reserve_tiles,
+                    limit_turn_budget,
+                    straight_travel,
+                )
+                    .chain(),
+            )
+        };
 
         #[cfg(feature = "path_finding")]
-        app.insert_resource(GridInfo::default());
-
-        app.add_observer(next_des);
+        app.insert_resource(GridInfo::default())
+            .init_resource::<FlowFields>()
+            .init_resource::<Replanning>()
+            .init_resource::<Reservations>()
+            .init_resource::<GridLinks>()
+            .add_systems(
+                PreUpdate,
+                (
+                    sync_grid_nav::<CardinalNeighborhood>,
+                    sync_grid_nav::<CardinalNeighborhood3d>,
+                    sync_grid_nav::<OrdinalNeighborhood>,
+                    sync_grid_nav::<OrdinalNeighborhood3d>,
+                    sync_grid_nav::<CardinalIsoNeighborhood>,
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    tag_grid_pathfinding,
+                    (
+                        pathfind_on_grids::<CardinalNeighborhood>,
+                        pathfind_on_grids::<CardinalNeighborhood3d>,
+                        pathfind_on_grids::<OrdinalNeighborhood>,
+                        pathfind_on_grids::<OrdinalNeighborhood3d>,
+                        pathfind_on_grids::<CardinalIsoNeighborhood>,
+                    ),
+                    (
+                        next_position_on_grids::<CardinalNeighborhood>,
+                        next_position_on_grids::<CardinalNeighborhood3d>,
+                        next_position_on_grids::<OrdinalNeighborhood>,
+                        next_position_on_grids::<OrdinalNeighborhood3d>,
+                        next_position_on_grids::<CardinalIsoNeighborhood>,
+                    ),
+                )
+                    .chain()
+                    .in_set(PathingSet),
+            )
+            .add_observer(pathfinding_failed)
+            .add_observer(smooth_northstar_path)
+            .add_observer(link_traversed)
+            .add_observer(cancel_link_route)
+            .add_observer(update_flow_fields_on_nav_change)
+            .add_observer(queue_replan_on_nav_change);
+
+        app.add_observer(next_des)
+            .add_observer(take_step)
+            .add_observer(advance_turn);
 
         if self.states.is_empty() {
-            app.add_systems(Update, systems);
+            app.add_systems(Update, systems());
         } else {
             for state in &self.states {
-                app.add_systems(Update, systems.run_if(in_state(state.clone())));
+                app.add_systems(Update, systems().run_if(in_state(state.clone())));
             }
         }
     }
@@ -143,17 +268,28 @@ impl LinearMovement {
     }
 }
 
-#[cfg(feature = "path_finding")]
-#[derive(Resource, Default)]
-pub struct GridInfo {
-    pub tile_size: Vec3,
-    pub grid_offset: Vec3,
+/// Pauses of movement by other components, apart from [`LinearMovement::is_freezed`] set by user
+#[derive(QueryData)]
+struct MovementPause {
+    turn: Option<&'static TurnBasedMovement>,
+    #[cfg(feature = "path_finding")]
+    reserve: Option<&'static ReserveTiles>,
+}
+
+impl MovementPauseItem<'_, '_> {
+    fn is_paused(&self) -> bool {
+        #[cfg(feature = "path_finding")]
+        if self.reserve.is_some_and(ReserveTiles::is_waiting) {
+            return true;
+        }
+        self.turn.is_some_and(TurnBasedMovement::is_paused)
+    }
 }
 
 #[cfg(not(any(feature = "collider_2d", feature = "collider_3d")))]
-fn straight_travel(time: Res<Time>, mut query: Query<(&mut Transform, &LinearMovement)>) {
-    for (mut transform, movement) in query.iter_mut() {
-        if movement.des.is_empty() || movement.is_freezed {
+fn straight_travel(time: Res<Time>, mut query: Query<(&mut Transform, &LinearMovement, MovementPause)>) {
+    for (mut transform, movement, pause) in query.iter_mut() {
+        if movement.des.is_empty() || movement.is_freezed || pause.is_paused() {
             continue;
         }
 
@@ -175,9 +311,12 @@ fn straight_travel(time: Res<Time>, mut query: Query<(&mut Transform, &LinearMov
 }
 
 #[cfg(any(feature = "collider_2d", feature = "collider_3d"))]
-fn straight_travel(mut query: Query<(&mut Transform, &mut LinearMovement, &mut LinearVelocity)>, time: Res<Time>) {
-    for (mut transform, mut movement, mut velocity) in query.iter_mut() {
-        if movement.is_stopped {
+fn straight_travel(
+    mut query: Query<(&mut Transform, &mut LinearMovement, &mut LinearVelocity, MovementPause)>,
+    time: Res<Time>,
+) {
+    for (mut transform, mut movement, mut velocity, pause) in query.iter_mut() {
+        if movement.is_stopped || pause.is_paused() {
             **velocity = Vector::ZERO;
             movement.is_stopped = false;
             continue;
@@ -206,19 +345,29 @@ fn straight_travel(mut query: Query<(&mut Transform, &mut LinearMovement, &mut L
     }
 }
 
+#[allow(clippy::type_complexity)]
 fn check_arrived(
     mut commands: Commands,
-    #[cfg(not(feature = "path_finding"))] mut query: Query<(&Transform, &mut LinearMovement, Entity, Entity, Entity)>,
+    #[cfg(not(feature = "path_finding"))] mut query: Query<(
+        &Transform,
+        &mut LinearMovement,
+        Entity,
+        Entity,
+        Entity,
+        MovementPause,
+    )>,
     #[cfg(feature = "path_finding")] mut query: Query<(
         &Transform,
         &mut LinearMovement,
         Entity,
-        &mut AgentPos,
-        &NextPos,
+        Option<&mut AgentPos>,
+        Option<&NextPos>,
+        MovementPause,
     )>,
+    #[cfg(feature = "path_finding")] grids: Grids,
 ) {
-    for (transform, mut movement, e, mut _agent_pos, _next_pos) in query.iter_mut() {
-        if movement.des.is_empty() || movement.is_freezed {
+    for (transform, mut movement, e, _agent_pos, _next_pos, pause) in query.iter_mut() {
+        if movement.des.is_empty() || movement.is_freezed || pause.is_paused() {
             continue;
         }
         let next_stop = movement.des.first().unwrap().pos + movement.offset;
@@ -245,24 +394,29 @@ fn check_arrived(
             movement.des.remove(0);
 
             #[cfg(feature = "path_finding")]
-            {
-                _agent_pos.0 = _next_pos.0;
-                commands.entity(e).remove::<NextPos>();
+            if let Some(mut agent_pos) = _agent_pos {
+                if let Some(next_pos) = _next_pos {
+                    agent_pos.0 = next_pos.0;
+                    commands.entity(e).remove::<NextPos>();
+                } else if let Some(tile) = grids.of_agent(e).world_to_tile(next_stop - movement.offset) {
+                    // Moved without path finding, e.g. `PathFallback::StraightLine`
+                    agent_pos.0 = tile;
+                }
             }
         }
     }
 }
 
 #[cfg(feature = "path_finding")]
-fn update_travel_stop(mut query: Query<(&NextPos, &mut LinearMovement)>, grid_info: Res<GridInfo>) {
-    for (next_pos, mut movement) in query.iter_mut() {
-        let next_pos_f = next_pos.0.as_vec3() * grid_info.tile_size + grid_info.grid_offset;
+fn update_travel_stop(mut query: Query<(Entity, &NextPos, &mut LinearMovement)>, grids: Grids) {
+    for (entity, next_pos, mut movement) in query.iter_mut() {
+        let next_des = grids.of_agent(entity).tile_destination(next_pos.0, movement.speed);
         if let Some(des) = movement.des.first() {
-            if next_pos_f != des.pos {
-                movement.des = vec![Destination::from_pos(next_pos_f)];
+            if next_des.pos != des.pos {
+                movement.des = vec![next_des];
             }
         } else {
-            movement.des = vec![Destination::from_pos(next_pos_f)];
+            movement.des = vec![next_des];
         }
     }
 }
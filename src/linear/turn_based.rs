@@ -0,0 +1,253 @@
+#[cfg(feature = "path_finding")]
+use crate::grid::{
+    GridInfo,
+    Grids,
+};
+use crate::linear::LinearMovement;
+use crate::{
+    AdvanceTurn,
+    Arrived,
+    StepTaken,
+};
+use bevy::prelude::{
+    Commands,
+    Component,
+    Entity,
+    On,
+    Query,
+    Transform,
+    Vec3,
+};
+
+/// Move in turns. Each step to the next destination consumes movement budget,
+/// which is the cost of tiles entered on the way on grid or `1` otherwise.
+/// Movement is paused when the budget is not enough for the next step, until [`AdvanceTurn`] is triggered.
+#[derive(Component, Clone, Debug)]
+pub struct TurnBasedMovement {
+    /// Budget given at each turn
+    pub budget_per_turn: u32,
+    /// Budget left in current turn
+    pub remaining: u32,
+    is_paused: bool,
+    /// Destination and cost of the current step, from where the step started
+    step: Option<(Vec3, u32)>,
+}
+
+impl TurnBasedMovement {
+    pub fn new(budget_per_turn: u32) -> Self {
+        Self {
+            budget_per_turn,
+            remaining: budget_per_turn,
+            is_paused: false,
+            step: None,
+        }
+    }
+
+    /// Refill the budget
+    pub fn advance_turn(&mut self) {
+        self.remaining = self.budget_per_turn;
+    }
+
+    /// Movement is paused because the budget is run out. [`LinearMovement`] is not frozen.
+    pub fn is_paused(&self) -> bool {
+        self.is_paused
+    }
+}
+
+pub(crate) fn limit_turn_budget(
+    mut query: Query<(Entity, &Transform, &LinearMovement, &mut TurnBasedMovement)>,
+    #[cfg(feature = "path_finding")] grids: Grids,
+) {
+    for (_entity, transform, movement, mut turn) in query.iter_mut() {
+        // Frozen by user
+        if movement.is_freezed {
+            continue;
+        }
+
+        let Some(des) = movement.des.first() else {
+            // Nothing to move to
+            if turn.is_paused || turn.step.is_some() {
+                turn.is_paused = false;
+                turn.step = None;
+            }
+            continue;
+        };
+        let cost = match turn.step {
+            Some((pos, cost)) if pos == des.pos => cost,
+            _ => {
+                let from = transform.translation - movement.offset;
+                #[cfg(feature = "path_finding")]
+                let cost = step_cost(grids.of_agent(_entity), from, des.pos);
+                #[cfg(not(feature = "path_finding"))]
+                let cost = step_cost(from, des.pos);
+                turn.step = Some((des.pos, cost));
+                cost
+            }
+        };
+
+        let is_paused = cost > turn.remaining;
+        if turn.is_paused != is_paused {
+            turn.is_paused = is_paused;
+        }
+    }
+}
+
+pub(crate) fn take_step(
+    trigger: On<Arrived>,
+    mut commands: Commands,
+    mut query: Query<(&LinearMovement, &mut TurnBasedMovement)>,
+    #[cfg(feature = "path_finding")] grids: Grids,
+) {
+    let entity = trigger.entity;
+    let Ok((movement, mut turn)) = query.get_mut(entity) else {
+        return;
+    };
+
+    let pos = trigger.pos - movement.offset;
+    let cost = match turn.step.take() {
+        Some((des, cost)) if des == pos => cost,
+        // Arrived without passing `limit_turn_budget`, charge the destination only
+        #[cfg(feature = "path_finding")]
+        _ => step_cost(grids.of_agent(entity), pos, pos),
+        #[cfg(not(feature = "path_finding"))]
+        _ => step_cost(pos, pos),
+    };
+
+    turn.remaining = turn.remaining.saturating_sub(cost);
+    commands.trigger(StepTaken {
+        entity,
+        pos: trigger.pos,
+        remaining: turn.remaining,
+    });
+}
+
+pub(crate) fn advance_turn(_: On<AdvanceTurn>, mut query: Query<&mut TurnBasedMovement>) {
+    for mut turn in query.iter_mut() {
+        turn.advance_turn();
+    }
+}
+
+/// Cost of tiles entered by moving straight from `from` to `to`
+#[cfg(feature = "path_finding")]
+fn step_cost(grid_info: &GridInfo, from: Vec3, to: Vec3) -> u32 {
+    let Some(to) = grid_info.world_to_tile(to) else {
+        return 1;
+    };
+    grid_info
+        .world_to_tile(from)
+        .filter(|from| *from != to)
+        .and_then(|from| grid_info.line_cost(from, to))
+        .or(grid_info.cost(to))
+        .unwrap_or(1)
+}
+
+#[cfg(not(feature = "path_finding"))]
+fn step_cost(_from: Vec3, _to: Vec3) -> u32 {
+    1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Destination;
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy::prelude::World;
+
+    fn world() -> World {
+        let mut world = World::new();
+        #[cfg(feature = "path_finding")]
+        {
+            let mut grid_info = GridInfo::default();
+            grid_info.tile_size = Vec3::ONE;
+            world.insert_resource(grid_info);
+        }
+        world.add_observer(take_step);
+        world.add_observer(advance_turn);
+        world
+    }
+
+    fn spawn(world: &mut World, des: &[Vec3], budget: u32) -> Entity {
+        world
+            .spawn((
+                Transform::default(),
+                LinearMovement {
+                    des: des.iter().map(|pos| Destination::from_pos(*pos)).collect(),
+                    ..Default::default()
+                },
+                TurnBasedMovement::new(budget),
+            ))
+            .id()
+    }
+
+    fn limit(world: &mut World) {
+        world.run_system_once(limit_turn_budget).unwrap();
+    }
+
+    fn turn(world: &World, entity: Entity) -> &TurnBasedMovement {
+        world.get::<TurnBasedMovement>(entity).unwrap()
+    }
+
+    #[test]
+    fn pause_until_next_turn() {
+        let mut world = world();
+        let entity = spawn(&mut world, &[Vec3::X, Vec3::new(2., 0., 0.)], 1);
+
+        limit(&mut world);
+        assert!(!turn(&world, entity).is_paused());
+        world.trigger(Arrived { entity, pos: Vec3::X });
+        world.get_mut::<LinearMovement>(entity).unwrap().des.remove(0);
+        world.get_mut::<Transform>(entity).unwrap().translation = Vec3::X;
+        assert_eq!(turn(&world, entity).remaining, 0);
+
+        limit(&mut world);
+        assert!(turn(&world, entity).is_paused());
+        world.trigger(AdvanceTurn);
+        limit(&mut world);
+        assert!(!turn(&world, entity).is_paused());
+    }
+
+    #[test]
+    fn resume_without_destination() {
+        let mut world = world();
+        let entity = spawn(&mut world, &[Vec3::X], 0);
+        limit(&mut world);
+        assert!(turn(&world, entity).is_paused());
+
+        world.get_mut::<LinearMovement>(entity).unwrap().des.clear();
+        limit(&mut world);
+        assert!(!turn(&world, entity).is_paused());
+    }
+
+    #[cfg(feature = "path_finding")]
+    #[test]
+    fn charge_tiles_along_step() {
+        use bevy::prelude::UVec3;
+        use bevy_northstar::prelude::{
+            CardinalGrid,
+            GridSettingsBuilder,
+            Nav,
+        };
+
+        let mut grid = CardinalGrid::new(&GridSettingsBuilder::new_2d(8, 8).chunk_size(4).build());
+        grid.set_nav(UVec3::new(1, 0, 0), Nav::Passable(3));
+        grid.set_nav(UVec3::new(2, 0, 0), Nav::Passable(2));
+        grid.build();
+        let mut world = world();
+        world.resource_mut::<GridInfo>().copy_nav(&grid);
+
+        // A smoothed step skipping the tile in between
+        let des = Vec3::new(2., 0., 0.);
+        let entity = spawn(&mut world, &[des], 4);
+        limit(&mut world);
+        assert!(turn(&world, entity).is_paused());
+
+        world.get_mut::<TurnBasedMovement>(entity).unwrap().remaining = 6;
+        limit(&mut world);
+        assert!(!turn(&world, entity).is_paused());
+        // The cost is decided where the step starts
+        world.get_mut::<Transform>(entity).unwrap().translation = Vec3::new(1.5, 0., 0.);
+        limit(&mut world);
+        world.trigger(Arrived { entity, pos: des });
+        assert_eq!(turn(&world, entity).remaining, 1);
+    }
+}
@@ -1,13 +1,81 @@
+pub mod formation;
+#[cfg(feature = "gamepad")]
+pub mod gamepad;
+pub mod hold;
+pub mod interaction;
+pub mod marker;
 #[cfg(feature = "path_finding")]
-use crate::linear::GridInfo;
+pub mod preview;
+mod ray_cast;
+pub mod selection;
+pub mod touch;
+#[cfg(feature = "ui_picking")]
+pub mod ui;
+
+#[cfg(feature = "path_finding")]
+use crate::grid::link::{
+    GridLinks,
+    GridPos,
+    LinkRoute,
+};
+#[cfg(feature = "path_finding")]
+use crate::grid::Grids;
+#[cfg(feature = "path_finding")]
+use crate::{
+    DestinationRejected,
+    PathNotFound,
+};
+#[cfg(feature = "path_finding")]
+use crate::mouse_control::formation::snap_to_tiles;
+use crate::mouse_control::formation::Formation;
+#[cfg(feature = "gamepad")]
+use crate::mouse_control::gamepad::update_virtual_cursor;
+use crate::mouse_control::interaction::{
+    follow_interaction,
+    pick_interactable,
+    Interactable,
+    InteractionOrder,
+};
+#[cfg(feature = "gizmos")]
+use crate::mouse_control::marker::draw_goal_chain;
+use crate::mouse_control::marker::sync_goal_markers;
+#[cfg(all(feature = "path_finding", feature = "gizmos"))]
+use crate::mouse_control::preview::draw_path_preview;
+#[cfg(feature = "path_finding")]
+use crate::mouse_control::preview::update_path_preview;
+use crate::mouse_control::hold::{
+    hold_to_move,
+    HoldToMove,
+};
+use crate::mouse_control::ray_cast::ClickRayCast;
+#[cfg(feature = "ui_picking")]
+use crate::mouse_control::ui::UiBlock;
+use crate::mouse_control::touch::{
+    update_pointer_input,
+    PointerInput,
+    TouchControl,
+};
+use crate::mouse_control::selection::{
+    select,
+    Selectable,
+    Selected,
+    Selection,
+};
 use crate::{
     Arrived,
     Destination,
     NextDes,
 };
 use bevy::app::Update;
+use bevy::camera::RenderTarget;
+use bevy::ecs::system::{
+    ScheduleSystem,
+    SystemParam,
+};
+use bevy::math::Vec2;
+use bevy::platform::collections::HashMap;
 #[cfg(feature = "path_finding")]
-use bevy::prelude::UVec3;
+use bevy::platform::collections::HashSet;
 use bevy::prelude::{
     in_state,
     App,
@@ -17,20 +85,38 @@ use bevy::prelude::{
     Component,
     Entity,
     GlobalTransform,
-    InfinitePlane3d,
+    Has,
     IntoScheduleConfigs,
     MouseButton,
     On,
     Plugin,
+    KeyCode,
     Query,
     Res,
+    Resource,
     States,
+    Transform,
     Vec3,
     Window,
     Without,
 };
+use bevy::window::{
+    PrimaryWindow,
+    WindowRef,
+};
+#[cfg(feature = "path_finding")]
+use crate::grid::flow_field::FlowFieldGoal;
 #[cfg(feature = "path_finding")]
-use bevy_northstar::prelude::Pathfind;
+use bevy::prelude::{
+    UVec3,
+    With,
+};
+#[cfg(feature = "path_finding")]
+use bevy_northstar::prelude::{
+    AgentPos,
+    NextPos,
+    Pathfind,
+};
 
 pub(crate) struct MouseControlMovementPlugin<T>
 where
@@ -46,6 +132,20 @@ where
     pub(crate) fn new(states: Vec<T>) -> Self {
         Self { states }
     }
+
+    /// Run the systems in any of the states, or always if no states are given
+    fn add_systems<M, S>(&self, app: &mut App, systems: impl Fn() -> S)
+    where
+        S: IntoScheduleConfigs<ScheduleSystem, M>,
+    {
+        if self.states.is_empty() {
+            app.add_systems(Update, systems());
+        } else {
+            for state in &self.states {
+                app.add_systems(Update, systems().run_if(in_state(state.clone())));
+            }
+        }
+    }
 }
 
 impl<T> Plugin for MouseControlMovementPlugin<T>
@@ -53,32 +153,129 @@ where
     T: States,
 {
     fn build(&self, app: &mut App) {
-        app.add_observer(next_des).add_observer(arrived);
+        app.init_resource::<Selection>()
+            .init_resource::<TouchControl>()
+            .init_resource::<PointerInput>()
+            .init_resource::<OrderModifiers>()
+            .add_observer(next_des)
+            .add_observer(arrived);
 
-        if self.states.is_empty() {
-            app.add_systems(Update, click);
-        } else {
-            for state in &self.states {
-                app.add_systems(Update, click.run_if(in_state(state.clone())));
-            }
-        }
+        #[cfg(feature = "path_finding")]
+        app.add_observer(path_not_found);
+
+        #[cfg(feature = "gizmos")]
+        self.add_systems(app, || draw_goal_chain);
+        #[cfg(all(feature = "path_finding", feature = "gizmos"))]
+        self.add_systems(app, || draw_path_preview);
+
+        let systems = || {
+            (
+                update_pointer_input,
+                hold_to_move,
+                select,
+                click,
+                follow_interaction,
+                sync_goal_markers,
+            )
+                .chain()
+        };
+        self.add_systems(app, systems);
+
+        #[cfg(feature = "gamepad")]
+        self.add_systems(app, || {
+            update_virtual_cursor.after(update_pointer_input).before(hold_to_move)
+        });
+
+        #[cfg(feature = "path_finding")]
+        self.add_systems(app, || update_path_preview.after(select).before(click));
     }
 }
 
-#[derive(Component, Default)]
+/// Surface to catch clicks in 3D. The nearest hit catcher with the highest priority is used.
+///
+/// The click is cast against avian colliders with `collider_3d` feature, meshes with `mesh_picking` feature,
+/// or the plane through the catcher facing its up direction otherwise.
+/// With `collider_3d`, other colliders in front of the catcher block the click.
+#[derive(Component)]
 pub struct ClickCatcher {
     pub offset: Vec3,
+    /// Cameras which this catcher catches clicks from. Empty means all cameras.
+    pub cameras: Vec<Entity>,
+    /// Half size on local X and Z axes of the catcher plane. Only used without raycast features.
+    pub bounds: Option<Vec2>,
+    /// Bit mask of layers. Catcher is used by objects with any same bit in `MouseMovementObject::click_layers`.
+    pub layers: u32,
+    /// Catcher with higher priority is used even if it's farther
+    pub priority: i32,
+}
+
+impl Default for ClickCatcher {
+    fn default() -> Self {
+        Self {
+            offset: Vec3::ZERO,
+            cameras: Vec::new(),
+            bounds: None,
+            layers: u32::MAX,
+            priority: 0,
+        }
+    }
+}
+
+/// Where the new goal of a click goes among queued goals
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OrderQueue {
+    /// Replace all goals
+    #[default]
+    Replace,
+    /// Go after the queued goals
+    Append,
+    /// Go before the queued goals
+    Prepend,
+}
+
+/// Modifier keys held while clicking to queue the goal.
+/// While `Selection::add_keys` are held, clicks of the selection button don't append goals of selectable movers
+/// because they add to the selection instead.
+#[derive(Resource)]
+pub struct OrderModifiers {
+    /// Default shift keys
+    pub append: Vec<KeyCode>,
+    /// Default control keys
+    pub prepend: Vec<KeyCode>,
+}
+
+impl Default for OrderModifiers {
+    fn default() -> Self {
+        Self {
+            append: vec![KeyCode::ShiftLeft, KeyCode::ShiftRight],
+            prepend: vec![KeyCode::ControlLeft, KeyCode::ControlRight],
+        }
+    }
 }
 
 #[derive(Component)]
 pub struct MouseMovementObject {
-    /// Push new destination to the chain instead of overwrite
+    /// Push new destination to the chain instead of overwrite, even without modifier keys
     pub is_chain: bool,
 
     pub goals: Vec<Vec3>,
 
     /// Which buttons will trigger movement. Default is MouseButton::Left.
     pub click_button: Vec<MouseButton>,
+
+    /// Keep moving toward the cursor while the button is held
+    pub hold: Option<HoldToMove>,
+
+    /// Cameras which clicks from move this object. Empty means all cameras.
+    pub cameras: Vec<Entity>,
+
+    /// Bit mask of [`ClickCatcher`] layers this object moves on. Default is all layers.
+    pub click_layers: u32,
+
+    /// Follow shared flow field instead of path finding per object.
+    /// Good for a big crowd moving to the same goal.
+    #[cfg(feature = "path_finding")]
+    pub flow_field: bool,
 }
 
 impl Default for MouseMovementObject {
@@ -87,77 +284,301 @@ impl Default for MouseMovementObject {
             is_chain: false,
             goals: Vec::new(),
             click_button: vec![MouseButton::Left],
+            hold: None,
+            cameras: Vec::new(),
+            click_layers: u32::MAX,
+            #[cfg(feature = "path_finding")]
+            flow_field: false,
         }
     }
 }
 
+impl MouseMovementObject {
+    #[cfg(feature = "path_finding")]
+    fn navigate(&self, commands: &mut Commands, entity: Entity, tile: UVec3) {
+        if self.flow_field {
+            commands
+                .entity(entity)
+                .remove::<(Pathfind, NextPos)>()
+                .insert(FlowFieldGoal(tile));
+        } else {
+            commands.entity(entity).insert(Pathfind::new(tile));
+        }
+    }
+}
+
+/// Camera under the cursor, on any window
+#[derive(SystemParam)]
+pub(crate) struct CursorCamera<'w, 's> {
+    windows: Query<'w, 's, (Entity, &'static Window, Has<PrimaryWindow>)>,
+    cameras: Query<'w, 's, (Entity, &'static Camera, &'static GlobalTransform, &'static RenderTarget)>,
+}
+
+impl CursorCamera<'_, '_> {
+    pub(crate) fn primary_window(&self) -> Option<Entity> {
+        self.windows
+            .iter()
+            .find(|(_, _, is_primary)| *is_primary)
+            .map(|(entity, ..)| entity)
+    }
+
+    /// Mouse cursor position and its window
+    pub(crate) fn cursor(&self) -> Option<(Entity, Vec2)> {
+        self.windows
+            .iter()
+            .find_map(|(entity, window, _)| Some((entity, window.cursor_position()?)))
+    }
+
+    /// Active camera whose viewport contains the pointer, the one rendered on top if overlapping.
+    /// Return the camera entity and the pointer position.
+    pub(crate) fn pick(&self, pointer: &PointerInput) -> Option<(Entity, Vec2)> {
+        let primary = self.primary_window();
+        let (window, cursor) = pointer.position?;
+
+        self.cameras
+            .iter()
+            .filter(|(_, camera, _, target)| {
+                let target_window = match target {
+                    RenderTarget::Window(WindowRef::Primary) => primary,
+                    RenderTarget::Window(WindowRef::Entity(entity)) => Some(*entity),
+                    _ => None,
+                };
+                camera.is_active
+                    && target_window == Some(window)
+                    && camera.logical_viewport_rect().is_some_and(|rect| rect.contains(cursor))
+            })
+            .max_by_key(|(_, camera, ..)| camera.order)
+            .map(|(entity, ..)| (entity, cursor))
+    }
+
+    pub(crate) fn get(&self, camera: Entity) -> Option<(&Camera, &GlobalTransform)> {
+        self.cameras
+            .get(camera)
+            .ok()
+            .map(|(_, camera, transform, _)| (camera, transform))
+    }
+}
+
+/// Grids and links between them, to find where a click leads
+#[cfg(feature = "path_finding")]
+#[derive(SystemParam)]
+struct Navigation<'w, 's> {
+    grids: Grids<'w, 's>,
+    links: Res<'w, GridLinks>,
+    agent_pos: Query<'w, 's, &'static AgentPos>,
+}
+
+#[cfg(feature = "path_finding")]
+impl Navigation<'_, '_> {
+    /// Links to go through from the agent to the tile on another grid
+    fn route(&self, entity: Entity, grid: Option<Entity>, tile: UVec3) -> Option<Vec<usize>> {
+        let agent_pos = self.agent_pos.get(entity).ok()?;
+        self.links.route(
+            |grid| self.grids.get(grid),
+            GridPos::new(self.grids.grid_of(entity), agent_pos.0),
+            GridPos::new(grid, tile),
+        )
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn click(
     mut commands: Commands,
-    mouse_btn: Res<ButtonInput<MouseButton>>,
-    camera_query: Query<(&Camera, &GlobalTransform)>,
-    click_catchers: Query<(&GlobalTransform, &ClickCatcher), Without<Camera>>,
-    windows: Query<&Window>,
-    mut linear_object: Query<(Entity, &mut MouseMovementObject)>,
-    #[cfg(feature = "path_finding")] grid_info: Res<GridInfo>,
+    pointer: Res<PointerInput>,
+    keys: Res<ButtonInput<KeyCode>>,
+    selection: Res<Selection>,
+    formation: Option<Res<Formation>>,
+    cursor_camera: CursorCamera,
+    mut ray_cast: ClickRayCast,
+    mut linear_object: Query<(
+        Entity,
+        &mut MouseMovementObject,
+        &Transform,
+        Has<Selectable>,
+        Has<Selected>,
+    )>,
+    interactables: Query<(Entity, &GlobalTransform, &Interactable)>,
+    #[cfg(feature = "path_finding")] navigation: Navigation,
+    #[cfg(feature = "ui_picking")] ui_block: UiBlock,
 ) {
-    let Ok((camera, camera_transform)) = camera_query.single() else {
+    // Click on UI
+    #[cfg(feature = "ui_picking")]
+    if ui_block.is_blocked() {
         return;
-    };
+    }
 
-    let Ok(window) = windows.single() else {
+    let mouse_btn = &pointer.buttons;
+    let Some((camera_entity, cursor_position)) = cursor_camera.pick(&pointer) else {
         return;
     };
-    let Some(cursor_position) = window.cursor_position() else {
+    let Some((camera, camera_transform)) = cursor_camera.get(camera_entity) else {
         return;
     };
 
-    let mut world_pos = Vec3::ZERO;
-    if cfg!(feature = "2d") {
-        let Ok(world_pos_2d) = camera.viewport_to_world_2d(camera_transform, cursor_position) else {
-            return;
-        };
+    let hits = ray_cast.cursor_hits(camera_entity, camera, camera_transform, cursor_position);
+    let target = pick_interactable(&interactables, camera, camera_transform, cursor_position);
 
-        world_pos = Vec3::new(world_pos_2d.x, world_pos_2d.y, 0.);
-    } else {
-        // Calculate a ray pointing from the camera into the world based on the cursor's position.
-        let Ok(ray) = camera.viewport_to_world(camera_transform, cursor_position) else {
-            return;
-        };
-        for (global_transform, click_catcher) in click_catchers.iter() {
-            // Calculate if and where the ray is hitting the feeder plane.
-            let Some(distance) = ray.intersect_plane(
-                global_transform.translation(),
-                InfinitePlane3d::new(global_transform.up()),
-            ) else {
-                continue;
-            };
-            world_pos = ray.get_point(distance) + click_catcher.offset;
-            break;
+    // Index of the best hit on layers of object
+    let click_hit =
+        |mv_object: &MouseMovementObject| hits.iter().position(|hit| hit.layers & mv_object.click_layers != 0);
+
+    #[cfg(feature = "path_finding")]
+    let grids = &navigation.grids;
+
+    // Clicking on a unit selects it
+    let is_selecting = selection.hovered().is_some() && mouse_btn.just_pressed(selection.button);
+    let is_adding = keys.any_pressed(selection.add_keys.iter().copied());
+    // Held button to keep moving toward the cursor
+    let is_following = |mv_object: &MouseMovementObject| mv_object.hold.as_ref().is_some_and(HoldToMove::is_due);
+    let is_clicked = |mv_object: &MouseMovementObject, is_selectable: bool| {
+        mv_object.click_button.iter().any(|button| {
+            if is_selectable && *button == selection.button {
+                // Pressing may start a drag box, so order on release
+                selection.is_ground_click()
+            } else {
+                mouse_btn.just_pressed(*button)
+            }
+        })
+    };
+    let is_ordered = |mv_object: &MouseMovementObject, is_selectable: bool, is_selected: bool| {
+        (!is_selectable || (is_selected && !is_selecting))
+            && (mv_object.cameras.is_empty() || mv_object.cameras.contains(&camera_entity))
+            && (is_clicked(mv_object, is_selectable) || (is_following(mv_object) && selection.drag_rect().is_none()))
+    };
+
+    // Spread movers ordered to the same position
+    let mut groups: Vec<Vec<(Entity, Vec3)>> = hits.iter().map(|_| Vec::new()).collect();
+    for (entity, mv_object, transform, is_selectable, is_selected) in linear_object.iter() {
+        if let Some(hit) = click_hit(mv_object).filter(|_| is_ordered(mv_object, is_selectable, is_selected)) {
+            groups[hit].push((entity, transform.translation));
+        }
+    }
+    let mut slots = HashMap::new();
+    #[cfg(feature = "path_finding")]
+    let mut taken: HashMap<Option<Entity>, HashSet<UVec3>> = HashMap::new();
+    if let Some(formation) = formation {
+        for (hit, movers) in hits.iter().zip(&groups) {
+            if movers.len() > 1 {
+                let assigned = formation.assign(hit.pos, movers);
+                // Spread on distinct tiles of the grid each mover goes to
+                #[cfg(feature = "path_finding")]
+                let assigned = {
+                    let mut by_grid: HashMap<Option<Entity>, HashMap<Entity, Vec3>> = HashMap::new();
+                    for (entity, slot) in assigned {
+                        let grid = grids.under(hit.pos).or(grids.grid_of(entity));
+                        by_grid.entry(grid).or_default().insert(entity, slot);
+                    }
+                    let mut assigned = HashMap::new();
+                    for (grid, mut grid_slots) in by_grid {
+                        snap_to_tiles(
+                            &mut grid_slots,
+                            hit.pos,
+                            grids.get(grid),
+                            taken.entry(grid).or_default(),
+                        );
+                        assigned.extend(grid_slots);
+                    }
+                    assigned
+                };
+                slots.extend(assigned);
+            }
         }
     }
 
-    for (entity, mut mv_object) in linear_object.iter_mut() {
-        if mouse_btn.any_just_pressed(mv_object.click_button.clone()) {
-            if !mv_object.is_chain {
+    for (entity, mut mv_object, _, is_selectable, is_selected) in linear_object.iter_mut() {
+        let hit = click_hit(&mv_object).filter(|_| is_ordered(&mv_object, is_selectable, is_selected));
+        if let Some(hit) = hit {
+            // Move to interact with the clicked entity
+            if let Some(target) = target.filter(|target| *target != entity) {
                 mv_object.goals.clear();
+                commands.entity(entity).insert(InteractionOrder::new(target));
+                continue;
+            }
+            commands.entity(entity).remove::<InteractionOrder>();
+
+            let click_pos = hits[hit].pos;
+            let world_pos = slots.get(&entity).copied().unwrap_or(click_pos);
+
+            // Grid entity under the cursor
+            #[cfg(feature = "path_finding")]
+            let grid_under = grids.under(click_pos);
+
+            #[cfg(feature = "path_finding")]
+            if grid_under.is_some_and(|grid| Some(grid) != grids.grid_of(entity)) {
+                // Go to another grid through links
+                let grid_info = grids.get(grid_under);
+                let route = grid_info
+                    .world_to_tile(world_pos)
+                    .and_then(|tile| Some((tile, navigation.route(entity, grid_under, tile)?)));
+                let Some((tile, route)) = route else {
+                    commands.trigger(DestinationRejected { entity, pos: world_pos });
+                    continue;
+                };
+                mv_object.goals = vec![grid_info.tile_to_world(tile)];
+                commands
+                    .entity(entity)
+                    .remove::<(Pathfind, FlowFieldGoal)>()
+                    .insert(LinkRoute::new(route, tile));
+                continue;
+            }
+
+            #[cfg(feature = "path_finding")]
+            let grid_info = grids.of_agent(entity);
+            #[cfg(feature = "path_finding")]
+            let Some(tile) = grid_info.world_to_tile(world_pos) else {
+                commands.trigger(DestinationRejected { entity, pos: world_pos });
+                continue;
+            };
+            // Snap to the tile center so that goal can be matched with `Arrived` position
+            #[cfg(feature = "path_finding")]
+            let world_pos = grid_info.tile_to_world(tile);
+
+            if is_following(&mv_object) && mv_object.goals.last() == Some(&world_pos) {
+                continue;
+            }
+            let queue = match pointer.queue {
+                _ if is_following(&mv_object) => OrderQueue::Replace,
+                // Keys adding to the selection don't queue when the button also selects
+                OrderQueue::Append
+                    if is_selectable && is_adding && mv_object.click_button.contains(&selection.button) =>
+                {
+                    OrderQueue::Replace
+                }
+                OrderQueue::Replace if mv_object.is_chain => OrderQueue::Append,
+                queue => queue,
+            };
+            // The new goal is where object goes now
+            let is_current = queue != OrderQueue::Append || mv_object.goals.is_empty();
+            match queue {
+                OrderQueue::Replace => mv_object.goals = vec![world_pos],
+                OrderQueue::Append => mv_object.goals.push(world_pos),
+                OrderQueue::Prepend => mv_object.goals.insert(0, world_pos),
             }
-            mv_object.goals.push(world_pos);
 
             if cfg!(not(feature = "path_finding")) {
-                let is_chain = mv_object.is_chain;
                 let next_des = NextDes {
                     entity,
                     des: Destination::from_pos(world_pos),
-                    is_chain,
+                    is_chain: !is_current,
                 };
                 commands.trigger(next_des);
+
+                // Chain the goals after the prepended one again
+                if queue == OrderQueue::Prepend {
+                    for pos in mv_object.goals.iter().skip(1) {
+                        commands.trigger(NextDes {
+                            entity,
+                            des: Destination::from_pos(*pos),
+                            is_chain: true,
+                        });
+                    }
+                }
             }
 
             #[cfg(feature = "path_finding")]
-            {
-                commands
-                    .entity(entity)
-                    .insert(Pathfind::new(world_pos_to_tile(&world_pos, &grid_info)));
+            if is_current {
+                commands.entity(entity).remove::<LinkRoute>();
+                mv_object.navigate(&mut commands, entity, tile);
             }
         }
     }
@@ -166,20 +587,21 @@ fn click(
 fn arrived(
     trigger: On<Arrived>,
     mut _commands: Commands,
-    mut query: Query<(&mut MouseMovementObject, Entity)>,
-    #[cfg(feature = "path_finding")] grid_info: Res<GridInfo>,
+    mut query: Query<(&mut MouseMovementObject, Entity), Without<InteractionOrder>>,
+    #[cfg(feature = "path_finding")] grids: Grids,
+    #[cfg(feature = "path_finding")] routes: Query<(), With<LinkRoute>>,
 ) {
     if let Ok((mut mv_obj, _entity)) = query.get_mut(trigger.entity) {
         if !mv_obj.goals.is_empty() && *mv_obj.goals.first().unwrap() == trigger.pos {
             mv_obj.goals.remove(0);
         }
 
+        // Link route finds the path itself until the last grid
         #[cfg(feature = "path_finding")]
-        {
-            if let Some(world_pos) = mv_obj.goals.first() {
-                _commands
-                    .entity(_entity)
-                    .insert(Pathfind::new(world_pos_to_tile(world_pos, &grid_info)));
+        if !routes.contains(_entity) {
+            let grid_info = grids.of_agent(_entity);
+            if let Some(tile) = mv_obj.goals.first().and_then(|pos| grid_info.world_to_tile(*pos)) {
+                mv_obj.navigate(&mut _commands, _entity, tile);
             }
         }
     }
@@ -199,11 +621,29 @@ fn next_des(trigger: On<NextDes>, mut query: Query<&mut MouseMovementObject>) {
 }
 
 #[cfg(feature = "path_finding")]
-fn world_pos_to_tile(pos: &Vec3, grid_info: &GridInfo) -> UVec3 {
-    let tile_pos = (pos - grid_info.grid_offset) / grid_info.tile_size;
-    UVec3::new(
-        tile_pos.x.round() as u32,
-        tile_pos.y.round() as u32,
-        tile_pos.z.round() as u32,
-    )
+fn path_not_found(
+    trigger: On<PathNotFound>,
+    mut commands: Commands,
+    mut query: Query<(&mut MouseMovementObject, Has<LinkRoute>)>,
+    grids: Grids,
+) {
+    if let Ok((mut mv_obj, has_route)) = query.get_mut(trigger.entity) {
+        // Goal on another grid can't be reached
+        if has_route {
+            mv_obj.goals.clear();
+            return;
+        }
+
+        let grid_info = grids.of_agent(trigger.entity);
+        if mv_obj.goals.first() == Some(&trigger.pos) {
+            mv_obj.goals.remove(0);
+        }
+
+        if let Some(pos) = trigger.fallback {
+            mv_obj.goals.insert(0, pos);
+        } else if let Some(tile) = mv_obj.goals.first().and_then(|pos| grid_info.world_to_tile(*pos)) {
+            // Continue with the next goal in chain
+            mv_obj.navigate(&mut commands, trigger.entity, tile);
+        }
+    }
 }
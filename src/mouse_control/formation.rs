@@ -0,0 +1,351 @@
+#[cfg(feature = "path_finding")]
+use crate::grid::GridInfo;
+use bevy::math::{
+    Vec2,
+    Vec3,
+};
+use bevy::platform::collections::HashMap;
+#[cfg(feature = "path_finding")]
+use bevy::platform::collections::HashSet;
+#[cfg(feature = "path_finding")]
+use bevy::prelude::UVec3;
+use bevy::prelude::{
+    Entity,
+    Resource,
+};
+use std::f32::consts::PI;
+
+/// Spread destinations of movers moved by the same click, facing the move direction.
+/// Insert this resource to enable it.
+#[derive(Resource, Clone, Debug)]
+pub struct Formation {
+    pub shape: FormationShape,
+    /// Distance between slots
+    pub spacing: f32,
+}
+
+#[derive(Default, Clone, Debug)]
+pub enum FormationShape {
+    /// Rows and columns of about the same count
+    #[default]
+    Box,
+    /// One row across the move direction
+    Line,
+    /// Leader at the clicked position, others behind to both sides
+    Wedge,
+    Circle,
+    /// Offsets of slots, `x` to the right and `y` forward. `spacing` is not applied.
+    /// Movers without slot go to the clicked position.
+    Custom(Vec<Vec2>),
+}
+
+impl Formation {
+    pub fn new(shape: FormationShape, spacing: f32) -> Self {
+        Self { shape, spacing }
+    }
+
+    /// Offsets of slots for `count` movers, `x` to the right and `y` forward
+    pub fn offsets(&self, count: usize) -> Vec<Vec2> {
+        let spacing = self.spacing;
+        match &self.shape {
+            FormationShape::Box => {
+                let cols = (count as f32).sqrt().ceil().max(1.) as usize;
+                let rows = count.div_ceil(cols);
+                (0..count)
+                    .map(|i| {
+                        let (row, col) = ((i / cols) as f32, (i % cols) as f32);
+                        Vec2::new(col - (cols - 1) as f32 / 2., (rows - 1) as f32 / 2. - row) * spacing
+                    })
+                    .collect()
+            }
+            FormationShape::Line => (0..count)
+                .map(|i| Vec2::new((i as f32 - (count - 1) as f32 / 2.) * spacing, 0.))
+                .collect(),
+            FormationShape::Wedge => (0..count)
+                .map(|i| {
+                    let row = i.div_ceil(2) as f32;
+                    let side = if i % 2 == 1 { -1. } else { 1. };
+                    Vec2::new(side * row, -row) * spacing
+                })
+                .collect(),
+            FormationShape::Circle => {
+                if count <= 1 {
+                    return vec![Vec2::ZERO; count];
+                }
+                // Adjacent slots are `spacing` apart
+                let radius = spacing / (2. * (PI / count as f32).sin());
+                (0..count)
+                    .map(|i| Vec2::from_angle(2. * PI * i as f32 / count as f32) * radius)
+                    .collect()
+            }
+            FormationShape::Custom(slots) => (0..count)
+                .map(|i| slots.get(i).copied().unwrap_or(Vec2::ZERO))
+                .collect(),
+        }
+    }
+
+    /// Destination of each mover, given its current position.
+    /// Movers take the nearest free slots so that their paths cross less.
+    pub fn assign(&self, center: Vec3, movers: &[(Entity, Vec3)]) -> HashMap<Entity, Vec3> {
+        if movers.is_empty() {
+            return HashMap::new();
+        }
+
+        let centroid = movers.iter().map(|(_, pos)| to_plane(*pos)).sum::<Vec2>() / movers.len() as f32;
+        let forward = (to_plane(center) - centroid).normalize_or(Vec2::Y);
+        let right = Vec2::new(forward.y, -forward.x);
+        let slots: Vec<Vec3> = self
+            .offsets(movers.len())
+            .into_iter()
+            .map(|offset| from_plane(to_plane(center) + right * offset.x + forward * offset.y, center))
+            .collect();
+
+        // Same order for the same movers
+        let mut movers = movers.to_vec();
+        movers.sort_by_key(|(entity, _)| *entity);
+
+        let mut pairs: Vec<(f32, usize, usize)> = movers
+            .iter()
+            .enumerate()
+            .flat_map(|(m, (_, pos))| {
+                slots
+                    .iter()
+                    .enumerate()
+                    .map(move |(s, slot)| (to_plane(*pos).distance_squared(to_plane(*slot)), m, s))
+            })
+            .collect();
+        pairs.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut assigned = HashMap::new();
+        let mut is_taken = vec![false; slots.len()];
+        for (_, m, s) in pairs {
+            if is_taken[s] || assigned.contains_key(&movers[m].0) {
+                continue;
+            }
+            is_taken[s] = true;
+            assigned.insert(movers[m].0, slots[s]);
+        }
+        assigned
+    }
+}
+
+/// Move slots to the centers of distinct passable tiles, skipping tiles in `taken`.
+/// Slots on impassable or already taken tiles go to the nearest free tile reachable from the tile at `center`.
+/// Slots without a free tile are removed, their movers go to `center`.
+#[cfg(feature = "path_finding")]
+pub fn snap_to_tiles(
+    slots: &mut HashMap<Entity, Vec3>,
+    center: Vec3,
+    grid_info: &GridInfo,
+    taken: &mut HashSet<UVec3>,
+) {
+    // Same tiles for the same slots
+    let mut entities: Vec<Entity> = slots.keys().copied().collect();
+    entities.sort();
+
+    let mut moved = Vec::new();
+    for entity in entities {
+        let tile = grid_info.world_to_tile(slots[&entity]);
+        match tile.filter(|tile| grid_info.is_passable(*tile) && taken.insert(*tile)) {
+            Some(tile) => {
+                slots.insert(entity, grid_info.tile_to_world(tile));
+            }
+            None => moved.push((entity, tile)),
+        }
+    }
+
+    let center = grid_info.world_to_tile(center);
+    for (entity, tile) in moved {
+        let free = center.and_then(|center| {
+            grid_info.nearest_reachable_by(center, tile.unwrap_or(center), |tile| !taken.contains(&tile))
+        });
+        match free {
+            Some(free) => {
+                taken.insert(free);
+                slots.insert(entity, grid_info.tile_to_world(free));
+            }
+            None => {
+                slots.remove(&entity);
+            }
+        }
+    }
+}
+
+/// Position on the ground plane, `y` is forward on screen
+fn to_plane(pos: Vec3) -> Vec2 {
+    if cfg!(feature = "2d") {
+        pos.truncate()
+    } else {
+        Vec2::new(pos.x, -pos.z)
+    }
+}
+
+fn from_plane(pos: Vec2, center: Vec3) -> Vec3 {
+    if cfg!(feature = "2d") {
+        pos.extend(center.z)
+    } else {
+        Vec3::new(pos.x, center.y, -pos.y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::prelude::World;
+
+    const SHAPES: [FormationShape; 4] = [
+        FormationShape::Box,
+        FormationShape::Line,
+        FormationShape::Wedge,
+        FormationShape::Circle,
+    ];
+
+    /// Smallest distance between two slots
+    fn min_distance(offsets: &[Vec2]) -> f32 {
+        let mut min = f32::INFINITY;
+        for (i, a) in offsets.iter().enumerate() {
+            for b in &offsets[i + 1..] {
+                min = min.min(a.distance(*b));
+            }
+        }
+        min
+    }
+
+    #[test]
+    fn slot_counts() {
+        for shape in SHAPES {
+            let formation = Formation::new(shape, 2.);
+            for count in [0, 1, 2, 5, 9, 10] {
+                assert_eq!(formation.offsets(count).len(), count, "{:?}", formation.shape);
+            }
+        }
+        assert_eq!(
+            Formation::new(FormationShape::Custom(vec![Vec2::X]), 2.)
+                .offsets(3)
+                .len(),
+            3
+        );
+    }
+
+    #[test]
+    fn slot_spacing() {
+        for shape in SHAPES {
+            // Wedge rows are `spacing` apart on both axes
+            let expected = if matches!(shape, FormationShape::Wedge) { 2. * 2f32.sqrt() } else { 2. };
+            let formation = Formation::new(shape, 2.);
+            for count in [2, 5, 9, 10] {
+                let distance = min_distance(&formation.offsets(count));
+                assert!(
+                    (distance - expected).abs() < 1e-4,
+                    "{:?} {count}: {distance}",
+                    formation.shape
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn slot_positions() {
+        let line = Formation::new(FormationShape::Line, 2.).offsets(3);
+        assert_eq!(line, vec![Vec2::new(-2., 0.), Vec2::ZERO, Vec2::new(2., 0.)]);
+
+        let wedge = Formation::new(FormationShape::Wedge, 1.).offsets(3);
+        assert_eq!(wedge, vec![Vec2::ZERO, Vec2::new(-1., -1.), Vec2::new(1., -1.)]);
+
+        // Box is centered on the clicked position
+        let sum: Vec2 = Formation::new(FormationShape::Box, 1.).offsets(9).into_iter().sum();
+        assert!(sum.length() < 1e-4);
+
+        let custom = Formation::new(FormationShape::Custom(vec![Vec2::X, Vec2::Y]), 5.).offsets(3);
+        assert_eq!(custom, vec![Vec2::X, Vec2::Y, Vec2::ZERO]);
+    }
+
+    fn movers(world: &mut World, positions: &[Vec2]) -> Vec<(Entity, Vec3)> {
+        positions
+            .iter()
+            .map(|pos| (world.spawn_empty().id(), from_plane(*pos, Vec3::ZERO)))
+            .collect()
+    }
+
+    #[test]
+    fn assign_distinct_slots() {
+        let mut world = World::new();
+        let movers = movers(&mut world, &[Vec2::ZERO, Vec2::X, Vec2::Y, Vec2::ONE]);
+        let center = from_plane(Vec2::new(0., 10.), Vec3::ZERO);
+        let assigned = Formation::new(FormationShape::Box, 2.).assign(center, &movers);
+
+        assert_eq!(assigned.len(), movers.len());
+        let slots: Vec<Vec2> = assigned.values().map(|slot| to_plane(*slot)).collect();
+        assert!((min_distance(&slots) - 2.).abs() < 1e-4);
+    }
+
+    #[test]
+    fn assign_stable_when_ordered_again() {
+        let mut world = World::new();
+        let mut movers = movers(&mut world, &[Vec2::ZERO, Vec2::X, Vec2::new(2., 0.), Vec2::new(3., 0.)]);
+        let formation = Formation::new(FormationShape::Line, 1.5);
+        let center = from_plane(Vec2::new(1.5, 10.), Vec3::ZERO);
+        let assigned = formation.assign(center, &movers);
+        assert_eq!(formation.assign(center, &movers), assigned);
+
+        // Same result in any order of movers
+        movers.reverse();
+        assert_eq!(formation.assign(center, &movers), assigned);
+
+        // Movers keep their order across the move direction, so their paths don't cross
+        movers.reverse();
+        for ((entity, _), x) in movers.iter().zip([-0.75, 0.75, 2.25, 3.75]) {
+            assert!((to_plane(assigned[entity]) - Vec2::new(x, 10.)).length() < 1e-4);
+        }
+    }
+
+    #[cfg(feature = "path_finding")]
+    #[test]
+    fn snap_slots_to_free_tiles() {
+        use bevy_northstar::prelude::{
+            CardinalGrid,
+            GridSettingsBuilder,
+            Nav,
+        };
+
+        let mut grid = CardinalGrid::new(&GridSettingsBuilder::new_2d(8, 8).chunk_size(4).build());
+        grid.set_nav(UVec3::new(5, 5, 0), Nav::Impassable);
+        grid.build();
+        let mut grid_info = GridInfo::default();
+        grid_info.tile_size = Vec3::ONE;
+        grid_info.copy_nav(&grid);
+
+        let mut world = World::new();
+        let entities: Vec<Entity> = (0..4).map(|_| world.spawn_empty().id()).collect();
+        let mut slots: HashMap<Entity, Vec3> = entities
+            .iter()
+            .zip([
+                Vec3::new(3.2, 4.1, 0.),
+                // Same tile as the first
+                Vec3::new(2.9, 3.8, 0.),
+                // Impassable
+                Vec3::new(5., 5., 0.),
+                // Outside the grid
+                Vec3::new(20., 4., 0.),
+            ])
+            .map(|(entity, slot)| (*entity, slot))
+            .collect();
+        let mut taken = HashSet::from([UVec3::new(4, 4, 0)]);
+        snap_to_tiles(&mut slots, Vec3::new(4., 4., 0.), &grid_info, &mut taken);
+
+        assert_eq!(slots.len(), 4);
+        let tiles: HashSet<UVec3> = slots
+            .values()
+            .map(|slot| grid_info.world_to_tile(*slot).unwrap())
+            .collect();
+        assert_eq!(tiles.len(), 4);
+        assert!(tiles.iter().all(|tile| grid_info.is_passable(*tile)));
+        assert!(!tiles.contains(&UVec3::new(4, 4, 0)));
+        assert_eq!(taken.len(), 5);
+
+        // Slots are moved to tile centers, one of the first two slots keeps its tile
+        let kept = Vec3::new(3., 4., 0.);
+        assert!(slots[&entities[0]] == kept || slots[&entities[1]] == kept);
+        assert!(slots.values().all(|slot| slot.round() == *slot));
+    }
+}
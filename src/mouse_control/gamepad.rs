@@ -0,0 +1,183 @@
+use crate::mouse_control::touch::PointerInput;
+use crate::mouse_control::CursorCamera;
+use bevy::input::gamepad::{
+    Gamepad,
+    GamepadAxis,
+    GamepadButton,
+};
+use bevy::math::Vec2;
+use bevy::prelude::{
+    MouseButton,
+    Query,
+    Res,
+    ResMut,
+    Resource,
+    Time,
+    Window,
+};
+
+/// Cursor moved by gamepad stick, clicking with gamepad buttons. Insert this resource to enable it.
+/// It's used until the mouse moves, and used again when the stick moves or a button is pressed.
+#[derive(Resource)]
+pub struct VirtualCursor {
+    /// Position in the primary window, in logical pixels
+    pub position: Vec2,
+    /// Pixels per second at full tilt
+    pub speed: f32,
+    /// Horizontal and vertical axes moving the cursor. Default left stick.
+    pub stick: [GamepadAxis; 2],
+    /// Stick tilt ignored
+    pub dead_zone: f32,
+    /// Gamepad buttons clicking as mouse buttons. Default `South` for left and `East` for right.
+    pub buttons: Vec<(GamepadButton, MouseButton)>,
+    is_active: bool,
+    last_mouse: Option<Vec2>,
+}
+
+impl Default for VirtualCursor {
+    fn default() -> Self {
+        Self {
+            position: Vec2::ZERO,
+            speed: 600.,
+            stick: [GamepadAxis::LeftStickX, GamepadAxis::LeftStickY],
+            dead_zone: 0.1,
+            buttons: vec![
+                (GamepadButton::South, MouseButton::Left),
+                (GamepadButton::East, MouseButton::Right),
+            ],
+            is_active: false,
+            last_mouse: None,
+        }
+    }
+}
+
+impl VirtualCursor {
+    /// Gamepad is controlling the cursor, e.g. to show the cursor sprite
+    pub fn is_active(&self) -> bool {
+        self.is_active
+    }
+}
+
+pub(crate) fn update_virtual_cursor(
+    virtual_cursor: Option<ResMut<VirtualCursor>>,
+    mut pointer: ResMut<PointerInput>,
+    gamepads: Query<&Gamepad>,
+    windows: Query<&Window>,
+    cursor_camera: CursorCamera,
+    time: Res<Time>,
+) {
+    let Some(mut virtual_cursor) = virtual_cursor else {
+        return;
+    };
+    let Some(window) = cursor_camera.primary_window() else {
+        return;
+    };
+
+    // Mouse takes over when it moves
+    let mouse = cursor_camera.cursor().map(|(_, pos)| pos);
+    if mouse.is_some() && mouse != virtual_cursor.last_mouse {
+        virtual_cursor.is_active = false;
+    }
+    virtual_cursor.last_mouse = mouse;
+
+    let [axis_x, axis_y] = virtual_cursor.stick;
+    for gamepad in gamepads.iter() {
+        // Release even after the mouse took over, otherwise the button stays pressed
+        for (button, mouse_button) in virtual_cursor.buttons.iter() {
+            if gamepad.just_released(*button) {
+                pointer.buttons.release(*mouse_button);
+            }
+        }
+
+        let tilt = Vec2::new(gamepad.get(axis_x).unwrap_or(0.), gamepad.get(axis_y).unwrap_or(0.));
+        let is_pressed = virtual_cursor
+            .buttons
+            .iter()
+            .any(|(button, _)| gamepad.just_pressed(*button));
+        if tilt.length() <= virtual_cursor.dead_zone && !is_pressed && !virtual_cursor.is_active {
+            continue;
+        }
+
+        if !virtual_cursor.is_active {
+            virtual_cursor.is_active = true;
+            if let Some(mouse) = mouse {
+                virtual_cursor.position = mouse;
+            }
+        }
+        if tilt.length() > virtual_cursor.dead_zone {
+            // Window y axis points down
+            let delta = Vec2::new(tilt.x, -tilt.y) * virtual_cursor.speed * time.delta_secs();
+            virtual_cursor.position += delta;
+        }
+        for (button, mouse_button) in virtual_cursor.buttons.iter() {
+            if gamepad.just_pressed(*button) {
+                pointer.buttons.press(*mouse_button);
+            }
+        }
+    }
+
+    if !virtual_cursor.is_active {
+        return;
+    }
+    if let Ok(window_info) = windows.get(window) {
+        virtual_cursor.position = virtual_cursor
+            .position
+            .clamp(Vec2::ZERO, Vec2::new(window_info.width(), window_info.height()));
+    }
+    pointer.position = Some((window, virtual_cursor.position));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy::prelude::World;
+    use bevy::window::PrimaryWindow;
+
+    fn update(world: &mut World) {
+        world.resource_mut::<PointerInput>().buttons.clear();
+        world.run_system_once(update_virtual_cursor).unwrap();
+        for mut gamepad in world.query::<&mut Gamepad>().iter_mut(world) {
+            gamepad.digital_mut().clear();
+        }
+    }
+
+    #[test]
+    fn release_after_mouse_takes_over() {
+        let mut world = World::new();
+        world.init_resource::<PointerInput>();
+        world.init_resource::<VirtualCursor>();
+        world.init_resource::<Time>();
+        let window = world.spawn((Window::default(), PrimaryWindow)).id();
+        let gamepad = world.spawn(Gamepad::default()).id();
+
+        // Press with the gamepad
+        world
+            .get_mut::<Gamepad>(gamepad)
+            .unwrap()
+            .digital_mut()
+            .press(GamepadButton::South);
+        update(&mut world);
+        assert!(world.resource::<VirtualCursor>().is_active());
+        assert!(world.resource::<PointerInput>().buttons.pressed(MouseButton::Left));
+
+        // Mouse moves while the gamepad button is held
+        world
+            .get_mut::<Window>(window)
+            .unwrap()
+            .set_cursor_position(Some(Vec2::new(10., 10.)));
+        update(&mut world);
+        assert!(!world.resource::<VirtualCursor>().is_active());
+
+        world
+            .get_mut::<Gamepad>(gamepad)
+            .unwrap()
+            .digital_mut()
+            .release(GamepadButton::South);
+        update(&mut world);
+        let pointer = world.resource::<PointerInput>();
+        assert!(pointer.buttons.just_released(MouseButton::Left));
+        assert!(!pointer.buttons.pressed(MouseButton::Left));
+        assert!(!world.resource::<VirtualCursor>().is_active());
+    }
+}
@@ -0,0 +1,112 @@
+#[cfg(feature = "path_finding")]
+use crate::grid::flow_field::FlowFieldGoal;
+#[cfg(feature = "path_finding")]
+use crate::grid::link::LinkRoute;
+use crate::linear::LinearMovement;
+use crate::mouse_control::touch::PointerInput;
+use crate::mouse_control::MouseMovementObject;
+use bevy::prelude::{
+    Commands,
+    DetectChangesMut,
+    Entity,
+    Query,
+    Res,
+    Time,
+};
+#[cfg(feature = "path_finding")]
+use bevy_northstar::prelude::{
+    NextPos,
+    Path,
+    Pathfind,
+};
+
+/// Keep moving toward the cursor while the click button is held
+#[derive(Clone, Debug)]
+pub struct HoldToMove {
+    /// Seconds between moving to the new cursor position
+    pub interval: f32,
+    /// Stop when the button is released after holding, otherwise finish moving to the last position.
+    /// A single click always moves to the clicked position.
+    pub stop_on_release: bool,
+    elapsed: f32,
+    is_due: bool,
+    /// Held long enough to follow the cursor, not a single click
+    is_holding: bool,
+}
+
+impl Default for HoldToMove {
+    fn default() -> Self {
+        Self {
+            interval: 0.1,
+            stop_on_release: true,
+            elapsed: 0.,
+            is_due: false,
+            is_holding: false,
+        }
+    }
+}
+
+impl HoldToMove {
+    pub fn new(interval: f32, stop_on_release: bool) -> Self {
+        Self {
+            interval,
+            stop_on_release,
+            ..Self::default()
+        }
+    }
+
+    /// It's time to move to the cursor again
+    pub(crate) fn is_due(&self) -> bool {
+        self.is_due
+    }
+}
+
+pub(crate) fn hold_to_move(
+    mut _commands: Commands,
+    pointer: Res<PointerInput>,
+    mut query: Query<(Entity, &mut MouseMovementObject, &mut LinearMovement)>,
+    time: Res<Time>,
+) {
+    for (_entity, mut mv_object, mut movement) in query.iter_mut() {
+        let Some(hold) = &mv_object.hold else {
+            continue;
+        };
+        let buttons = mv_object.click_button.iter().copied();
+        let is_just_pressed = pointer.buttons.any_just_pressed(buttons.clone());
+        let is_pressed = pointer.buttons.any_pressed(buttons.clone());
+        let is_just_released = pointer.buttons.any_just_released(buttons);
+        if !hold.is_due && !is_pressed && !is_just_released {
+            continue;
+        }
+
+        // Hold timing is internal, it doesn't mark the object as changed
+        let Some(hold) = &mut mv_object.bypass_change_detection().hold else {
+            continue;
+        };
+        hold.is_due = false;
+        let mut is_stopped = false;
+        if is_just_pressed {
+            hold.elapsed = 0.;
+            hold.is_holding = false;
+        } else if is_pressed {
+            hold.elapsed += time.delta_secs();
+            if hold.elapsed >= hold.interval {
+                hold.elapsed = 0.;
+                hold.is_due = true;
+                hold.is_holding = true;
+            }
+        } else if is_just_released && hold.is_holding && hold.stop_on_release {
+            hold.is_holding = false;
+            is_stopped = true;
+        }
+
+        if is_stopped {
+            mv_object.goals.clear();
+            movement.stop();
+            #[cfg(feature = "path_finding")]
+            _commands
+                .entity(_entity)
+                .remove::<(Pathfind, NextPos, Path, FlowFieldGoal, LinkRoute)>();
+        }
+    }
+}
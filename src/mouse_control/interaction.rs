@@ -0,0 +1,247 @@
+#[cfg(feature = "path_finding")]
+use crate::grid::flow_field::FlowFieldGoal;
+#[cfg(feature = "path_finding")]
+use crate::grid::Grids;
+use crate::linear::LinearMovement;
+use crate::mouse_control::MouseMovementObject;
+use crate::InteractionReached;
+#[cfg(feature = "path_finding")]
+use crate::PathNotFound;
+#[cfg(not(feature = "path_finding"))]
+use crate::{
+    Destination,
+    NextDes,
+};
+use bevy::math::{
+    Vec2,
+    Vec3,
+    Vec3Swizzles,
+};
+use bevy::prelude::{
+    Camera,
+    Commands,
+    Component,
+    Entity,
+    GlobalTransform,
+    Query,
+    Transform,
+};
+#[cfg(feature = "path_finding")]
+use bevy::prelude::{
+    Or,
+    With,
+};
+#[cfg(feature = "path_finding")]
+use bevy_northstar::prelude::{
+    NextPos,
+    Path,
+    Pathfind,
+};
+
+/// Clicking on this entity orders objects to move to it and interact
+#[derive(Component, Clone, Debug)]
+pub struct Interactable {
+    /// Distance on the ground to interact from.
+    /// With path finding, it should be at least the tile size if the entity stands on an impassable tile.
+    pub range: f32,
+    /// Max distance in pixels from the cursor to the entity to click on it
+    pub pick_radius: f32,
+}
+
+impl Default for Interactable {
+    fn default() -> Self {
+        Self {
+            range: 1.,
+            pick_radius: 16.,
+        }
+    }
+}
+
+/// Moving to interact with the target
+#[derive(Component, Clone, Debug)]
+pub struct InteractionOrder {
+    pub target: Entity,
+    /// Target position when the object last moved to it
+    last_target_pos: Option<Vec3>,
+}
+
+impl InteractionOrder {
+    pub fn new(target: Entity) -> Self {
+        Self {
+            target,
+            last_target_pos: None,
+        }
+    }
+}
+
+/// Interactable entity nearest to the cursor on screen
+pub(crate) fn pick_interactable(
+    interactables: &Query<(Entity, &GlobalTransform, &Interactable)>,
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    cursor: Vec2,
+) -> Option<Entity> {
+    interactables
+        .iter()
+        .filter_map(|(entity, transform, interactable)| {
+            let pos = camera
+                .world_to_viewport(camera_transform, transform.translation())
+                .ok()?;
+            let distance = pos.distance(cursor);
+            (distance <= interactable.pick_radius).then_some((entity, distance))
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(entity, _)| entity)
+}
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn follow_interaction(
+    mut commands: Commands,
+    mut query: Query<(
+        Entity,
+        &mut InteractionOrder,
+        &Transform,
+        &mut LinearMovement,
+        &mut MouseMovementObject,
+    )>,
+    targets: Query<(&GlobalTransform, &Interactable)>,
+    #[cfg(feature = "path_finding")] grids: Grids,
+    #[cfg(feature = "path_finding")] pathing: Query<(), Or<(With<Pathfind>, With<FlowFieldGoal>)>>,
+) {
+    for (entity, mut order, transform, mut movement, mut mv_object) in query.iter_mut() {
+        let Ok((target_transform, interactable)) = targets.get(order.target) else {
+            commands.entity(entity).remove::<InteractionOrder>();
+            continue;
+        };
+        let target_pos = target_transform.translation();
+        let pos = transform.translation - movement.offset;
+
+        if ground_distance(pos, target_pos) <= interactable.range {
+            movement.stop();
+            mv_object.goals.clear();
+            commands.entity(entity).remove::<InteractionOrder>();
+            #[cfg(feature = "path_finding")]
+            commands.entity(entity).remove::<(Pathfind, NextPos, Path)>();
+            commands.trigger(InteractionReached {
+                entity,
+                target: order.target,
+            });
+            continue;
+        }
+
+        // Move again only when the target moved away, or stopped out of range with path finding
+        let is_moved = order
+            .last_target_pos
+            .is_none_or(|last| ground_distance(last, target_pos) > interactable.range / 2.);
+        #[cfg(feature = "path_finding")]
+        let is_moved = is_moved || (movement.des.is_empty() && !pathing.contains(entity));
+        if !is_moved {
+            continue;
+        }
+        order.last_target_pos = Some(target_pos);
+
+        #[cfg(feature = "path_finding")]
+        {
+            // Target usually stands on an impassable tile, go next to it
+            let grid_info = grids.of_agent(entity);
+            let goal = grid_info.world_to_tile(pos).zip(grid_info.world_to_tile(target_pos));
+            if let Some((start, target)) = goal {
+                let tile = grid_info.nearest_reachable(start, target).unwrap_or(target);
+                // Can't get any closer
+                if tile == start {
+                    movement.stop();
+                    mv_object.goals.clear();
+                    commands
+                        .entity(entity)
+                        .remove::<(InteractionOrder, Pathfind, NextPos, Path)>();
+                    commands.trigger(PathNotFound {
+                        entity,
+                        pos: target_pos,
+                        fallback: None,
+                    });
+                    continue;
+                }
+                mv_object.navigate(&mut commands, entity, tile);
+            }
+        }
+
+        #[cfg(not(feature = "path_finding"))]
+        commands.trigger(NextDes {
+            entity,
+            des: Destination::from_pos(target_pos),
+            is_chain: false,
+        });
+    }
+}
+
+/// Distance ignoring height
+fn ground_distance(a: Vec3, b: Vec3) -> f32 {
+    if cfg!(feature = "2d") {
+        a.truncate().distance(b.truncate())
+    } else {
+        a.xz().distance(b.xz())
+    }
+}
+
+#[cfg(all(test, feature = "path_finding"))]
+mod tests {
+    use super::*;
+    use crate::grid::GridInfo;
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy::prelude::{
+        On,
+        ResMut,
+        Resource,
+        UVec3,
+        World,
+    };
+    use bevy_northstar::prelude::{
+        CardinalGrid,
+        GridSettingsBuilder,
+        Nav,
+    };
+
+    #[derive(Resource, Default)]
+    struct NotFound(usize);
+
+    #[test]
+    fn give_up_out_of_range() {
+        // Wall at x = 3 between the agent and the target
+        let mut grid = CardinalGrid::new(&GridSettingsBuilder::new_2d(8, 8).chunk_size(4).build());
+        for y in 0..8 {
+            grid.set_nav(UVec3::new(3, y, 0), Nav::Impassable);
+        }
+        grid.build();
+        let mut grid_info = GridInfo::default();
+        grid_info.tile_size = Vec3::ONE;
+        grid_info.copy_nav(&grid);
+
+        let mut world = World::new();
+        world.insert_resource(grid_info);
+        world.init_resource::<NotFound>();
+        world.add_observer(|_: On<PathNotFound>, mut not_found: ResMut<NotFound>| not_found.0 += 1);
+        let target = world
+            .spawn((GlobalTransform::from_xyz(6., 0., 0.), Interactable::default()))
+            .id();
+        let agent = world
+            .spawn((
+                Transform::default(),
+                LinearMovement::default(),
+                MouseMovementObject::default(),
+                InteractionOrder::new(target),
+            ))
+            .id();
+
+        // Go to the nearest reachable tile
+        world.run_system_once(follow_interaction).unwrap();
+        let goal = world.get::<Pathfind>(agent).map(|pathfind| pathfind.goal);
+        assert_eq!(goal, Some(UVec3::new(2, 0, 0)));
+
+        // Arrived there, but still out of range
+        world.entity_mut(agent).remove::<Pathfind>();
+        world.get_mut::<Transform>(agent).unwrap().translation = Vec3::new(2., 0., 0.);
+        world.run_system_once(follow_interaction).unwrap();
+        assert!(world.get::<InteractionOrder>(agent).is_none());
+        assert_eq!(world.resource::<NotFound>().0, 1);
+    }
+}
@@ -0,0 +1,106 @@
+use crate::mouse_control::MouseMovementObject;
+use bevy::math::Vec3;
+use bevy::platform::collections::HashMap;
+#[cfg(feature = "gizmos")]
+use bevy::prelude::{
+    Color,
+    Gizmos,
+    Res,
+    Transform,
+};
+use bevy::prelude::{
+    Bundle,
+    Changed,
+    Commands,
+    Entity,
+    Query,
+    RemovedComponents,
+    ResMut,
+    Resource,
+};
+
+type SpawnMarker = Box<dyn Fn(&mut Commands, Vec3) -> Entity + Send + Sync>;
+
+/// Spawn a marker at each goal of [`MouseMovementObject`]. It's despawned when the goal is reached or canceled.
+/// Insert this resource to enable it.
+#[derive(Resource)]
+pub struct GoalMarkers {
+    /// Draw lines through queued goals
+    #[cfg(feature = "gizmos")]
+    pub chain_color: Option<Color>,
+    spawn: SpawnMarker,
+    markers: HashMap<Entity, Vec<(Vec3, Entity)>>,
+}
+
+impl GoalMarkers {
+    /// `bundle` makes the marker at the goal position, e.g. a sprite with `Transform::from_translation(pos)`
+    pub fn new<B: Bundle>(bundle: impl Fn(Vec3) -> B + Send + Sync + 'static) -> Self {
+        Self {
+            #[cfg(feature = "gizmos")]
+            chain_color: None,
+            spawn: Box::new(move |commands, pos| commands.spawn(bundle(pos)).id()),
+            markers: HashMap::new(),
+        }
+    }
+}
+
+pub(crate) fn sync_goal_markers(
+    mut commands: Commands,
+    goal_markers: Option<ResMut<GoalMarkers>>,
+    query: Query<(Entity, &MouseMovementObject), Changed<MouseMovementObject>>,
+    mut removed: RemovedComponents<MouseMovementObject>,
+) {
+    let Some(goal_markers) = goal_markers else {
+        return;
+    };
+    let goal_markers = goal_markers.into_inner();
+
+    for entity in removed.read() {
+        for (_, marker) in goal_markers.markers.remove(&entity).unwrap_or_default() {
+            commands.entity(marker).try_despawn();
+        }
+    }
+
+    for (entity, mv_object) in query.iter() {
+        let mut old = goal_markers.markers.remove(&entity).unwrap_or_default();
+        let mut markers = Vec::with_capacity(mv_object.goals.len());
+        for goal in mv_object.goals.iter() {
+            if let Some(i) = old.iter().position(|(pos, _)| pos == goal) {
+                markers.push(old.swap_remove(i));
+            } else {
+                markers.push((*goal, (goal_markers.spawn)(&mut commands, *goal)));
+            }
+        }
+        for (_, marker) in old {
+            commands.entity(marker).try_despawn();
+        }
+
+        if !markers.is_empty() {
+            goal_markers.markers.insert(entity, markers);
+        }
+    }
+}
+
+#[cfg(feature = "gizmos")]
+pub(crate) fn draw_goal_chain(
+    mut gizmos: Gizmos,
+    goal_markers: Option<Res<GoalMarkers>>,
+    query: Query<(&Transform, &MouseMovementObject)>,
+) {
+    let Some(color) = goal_markers.and_then(|goal_markers| goal_markers.chain_color) else {
+        return;
+    };
+
+    for (transform, mv_object) in query.iter() {
+        // Single goal is not a chain
+        if mv_object.goals.is_empty() || (mv_object.goals.len() == 1 && !mv_object.is_chain) {
+            continue;
+        }
+        let points = std::iter::once(transform.translation).chain(mv_object.goals.iter().copied());
+        if cfg!(feature = "2d") {
+            gizmos.linestrip_2d(points.map(|pos| pos.truncate()), color);
+        } else {
+            gizmos.linestrip(points, color);
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use crate::grid::Grids;
+use crate::mouse_control::ray_cast::ClickRayCast;
+use crate::mouse_control::selection::{
+    Selectable,
+    Selected,
+    Selection,
+};
+use crate::mouse_control::touch::PointerInput;
+#[cfg(feature = "ui_picking")]
+use crate::mouse_control::ui::UiBlock;
+use crate::mouse_control::{
+    CursorCamera,
+    MouseMovementObject,
+};
+use bevy::math::Vec3;
+#[cfg(feature = "gizmos")]
+use bevy::prelude::{
+    Color,
+    Gizmos,
+    Transform,
+};
+use bevy::prelude::{
+    Entity,
+    Has,
+    Query,
+    Res,
+    ResMut,
+    Resource,
+    Time,
+};
+use bevy_northstar::prelude::AgentPos;
+
+/// Path from the mover to the tile under the cursor
+#[derive(Clone, Debug, PartialEq)]
+pub enum PreviewPath {
+    /// World positions of the tiles on the path, from the mover's tile to the goal tile
+    Reachable(Vec<Vec3>),
+    /// Clicking here doesn't find a path. The position is the goal tile, or the cursor position if it's out of the grid.
+    Unreachable(Vec3),
+}
+
+/// Preview the path of the selected mover to the tile under the cursor before clicking.
+/// Insert this resource to enable it.
+///
+/// The preview is searched on [`crate::grid::GridInfo`], so it may differ from the path Northstar finds with the same cost.
+/// Paths to other grids through links are not previewed.
+#[derive(Resource)]
+pub struct PathPreview {
+    /// Seconds between searching the path again
+    pub interval: f32,
+    /// Draw the reachable path
+    #[cfg(feature = "gizmos")]
+    pub color: Option<Color>,
+    /// Draw a line from the mover to the unreachable goal
+    #[cfg(feature = "gizmos")]
+    pub unreachable_color: Option<Color>,
+    elapsed: f32,
+    mover: Option<Entity>,
+    path: Option<PreviewPath>,
+}
+
+impl Default for PathPreview {
+    fn default() -> Self {
+        Self {
+            interval: 0.1,
+            #[cfg(feature = "gizmos")]
+            color: None,
+            #[cfg(feature = "gizmos")]
+            unreachable_color: None,
+            elapsed: 0.,
+            mover: None,
+            path: None,
+        }
+    }
+}
+
+impl PathPreview {
+    /// Object the path is previewed for. Selected objects are preferred over objects without [`Selectable`].
+    pub fn mover(&self) -> Option<Entity> {
+        self.mover
+    }
+
+    /// `None` if the cursor is not over a place the mover can be ordered to
+    pub fn path(&self) -> Option<&PreviewPath> {
+        self.path.as_ref()
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub(crate) fn update_path_preview(
+    preview: Option<ResMut<PathPreview>>,
+    pointer: Res<PointerInput>,
+    selection: Res<Selection>,
+    cursor_camera: CursorCamera,
+    mut ray_cast: ClickRayCast,
+    movers: Query<(Entity, &MouseMovementObject, &AgentPos, Has<Selectable>, Has<Selected>)>,
+    grids: Grids,
+    time: Res<Time>,
+    #[cfg(feature = "ui_picking")] ui_block: UiBlock,
+) {
+    let Some(mut preview) = preview else {
+        return;
+    };
+    preview.elapsed += time.delta_secs();
+    if preview.elapsed < preview.interval {
+        return;
+    }
+    preview.elapsed = 0.;
+    preview.mover = None;
+    preview.path = None;
+
+    #[cfg(feature = "ui_picking")]
+    if ui_block.is_blocked() {
+        return;
+    }
+    // Clicking on a unit selects it instead of moving
+    if selection.hovered().is_some() {
+        return;
+    }
+
+    let Some((camera_entity, cursor_position)) = cursor_camera.pick(&pointer) else {
+        return;
+    };
+    let Some((camera, camera_transform)) = cursor_camera.get(camera_entity) else {
+        return;
+    };
+    let mover = movers
+        .iter()
+        .filter(|(_, mv_object, _, is_selectable, is_selected)| {
+            (!is_selectable || *is_selected)
+                && (mv_object.cameras.is_empty() || mv_object.cameras.contains(&camera_entity))
+        })
+        .max_by_key(|(.., is_selected)| *is_selected);
+    let Some((entity, mv_object, agent_pos, ..)) = mover else {
+        return;
+    };
+    let hits = ray_cast.cursor_hits(camera_entity, camera, camera_transform, cursor_position);
+    let Some(hit) = hits.iter().find(|hit| hit.layers & mv_object.click_layers != 0) else {
+        return;
+    };
+    if grids
+        .under(hit.pos)
+        .is_some_and(|grid| Some(grid) != grids.grid_of(entity))
+    {
+        return;
+    }
+
+    let grid_info = grids.of_agent(entity);
+    let path = match grid_info.world_to_tile(hit.pos) {
+        Some(goal) => match grid_info.find_path(agent_pos.0, goal) {
+            Some(path) => PreviewPath::Reachable(path.into_iter().map(|tile| grid_info.tile_to_world(tile)).collect()),
+            None => PreviewPath::Unreachable(grid_info.tile_to_world(goal)),
+        },
+        None => PreviewPath::Unreachable(hit.pos),
+    };
+    preview.mover = Some(entity);
+    preview.path = Some(path);
+}
+
+#[cfg(feature = "gizmos")]
+pub(crate) fn draw_path_preview(mut gizmos: Gizmos, preview: Option<Res<PathPreview>>, transforms: Query<&Transform>) {
+    let Some(preview) = preview else {
+        return;
+    };
+
+    match preview.path() {
+        Some(PreviewPath::Reachable(path)) => {
+            let Some(color) = preview.color else {
+                return;
+            };
+            if cfg!(feature = "2d") {
+                gizmos.linestrip_2d(path.iter().map(|pos| pos.truncate()), color);
+            } else {
+                gizmos.linestrip(path.iter().copied(), color);
+            }
+        }
+        Some(PreviewPath::Unreachable(goal)) => {
+            let Some(color) = preview.unreachable_color else {
+                return;
+            };
+            let Some(transform) = preview.mover.and_then(|mover| transforms.get(mover).ok()) else {
+                return;
+            };
+            if cfg!(feature = "2d") {
+                gizmos.line_2d(transform.translation.truncate(), goal.truncate(), color);
+            } else {
+                gizmos.line(transform.translation, *goal, color);
+            }
+        }
+        None => {}
+    }
+}
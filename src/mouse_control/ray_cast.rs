@@ -0,0 +1,147 @@
+use crate::mouse_control::ClickCatcher;
+#[cfg(feature = "collider_3d")]
+use avian3d::prelude::{
+    SpatialQuery,
+    SpatialQueryFilter,
+};
+use bevy::ecs::system::SystemParam;
+use bevy::math::{
+    Ray3d,
+    Vec2,
+};
+#[cfg(all(feature = "mesh_picking", not(feature = "collider_3d")))]
+use bevy::picking::mesh_picking::ray_cast::{
+    MeshRayCast,
+    MeshRayCastSettings,
+};
+#[cfg(not(any(feature = "collider_3d", feature = "mesh_picking")))]
+use bevy::prelude::InfinitePlane3d;
+use bevy::prelude::{
+    Camera,
+    Entity,
+    GlobalTransform,
+    Query,
+    Vec3,
+    Without,
+};
+
+/// Where the cursor ray hits a [`ClickCatcher`]
+pub(crate) struct ClickHit {
+    pub(crate) pos: Vec3,
+    pub(crate) layers: u32,
+}
+
+/// Cast the cursor ray against click catchers.
+/// Use avian colliders with `collider_3d` feature, meshes with `mesh_picking` feature,
+/// or planes of catchers otherwise.
+#[derive(SystemParam)]
+pub(crate) struct ClickRayCast<'w, 's> {
+    catchers: Query<'w, 's, (Entity, &'static GlobalTransform, &'static ClickCatcher), Without<Camera>>,
+    #[cfg(feature = "collider_3d")]
+    spatial_query: SpatialQuery<'w, 's>,
+    #[cfg(all(feature = "mesh_picking", not(feature = "collider_3d")))]
+    mesh_ray_cast: MeshRayCast<'w, 's>,
+}
+
+impl ClickRayCast<'_, '_> {
+    /// Positions under the cursor in the camera viewport, best first.
+    /// With `2d` feature, it's the single world position on the z = 0 plane.
+    pub(crate) fn cursor_hits(
+        &mut self,
+        camera_entity: Entity,
+        camera: &Camera,
+        camera_transform: &GlobalTransform,
+        cursor: Vec2,
+    ) -> Vec<ClickHit> {
+        if cfg!(feature = "2d") {
+            let Ok(world_pos_2d) = camera.viewport_to_world_2d(camera_transform, cursor) else {
+                return Vec::new();
+            };
+
+            vec![ClickHit {
+                pos: Vec3::new(world_pos_2d.x, world_pos_2d.y, 0.),
+                layers: u32::MAX,
+            }]
+        } else {
+            // Calculate a ray pointing from the camera into the world based on the cursor's position.
+            let Ok(ray) = camera.viewport_to_world(camera_transform, cursor) else {
+                return Vec::new();
+            };
+            self.hits(ray, camera_entity)
+        }
+    }
+
+    /// Catchers hit by the ray from the camera, higher priority first, then nearer
+    pub(crate) fn hits(&mut self, ray: Ray3d, camera: Entity) -> Vec<ClickHit> {
+        let catchers = &self.catchers;
+        let is_catcher = |entity: Entity| {
+            catchers
+                .get(entity)
+                .is_ok_and(|(_, _, catcher)| catcher.cameras.is_empty() || catcher.cameras.contains(&camera))
+        };
+        // Entity and distance along the ray
+        let mut hits: Vec<(Entity, f32)> = Vec::new();
+
+        #[cfg(feature = "collider_3d")]
+        {
+            // Colliders which are not catchers, e.g. walls and units, block the click
+            let mut blocked_at = f32::MAX;
+            self.spatial_query.ray_hits_callback(
+                ray.origin,
+                ray.direction,
+                f32::MAX,
+                true,
+                &SpatialQueryFilter::default(),
+                |hit| {
+                    if is_catcher(hit.entity) {
+                        hits.push((hit.entity, hit.distance));
+                    } else if !catchers.contains(hit.entity) {
+                        blocked_at = blocked_at.min(hit.distance);
+                    }
+                    true
+                },
+            );
+            hits.retain(|(_, distance)| *distance <= blocked_at);
+        }
+
+        #[cfg(all(feature = "mesh_picking", not(feature = "collider_3d")))]
+        {
+            let settings = MeshRayCastSettings::default()
+                .never_early_exit()
+                .with_filter(&is_catcher);
+            let mesh_hits = self.mesh_ray_cast.cast_ray(ray, &settings);
+            hits.extend(mesh_hits.iter().map(|(entity, hit)| (*entity, hit.distance)));
+        }
+
+        #[cfg(not(any(feature = "collider_3d", feature = "mesh_picking")))]
+        for (entity, transform, catcher) in catchers.iter() {
+            if !is_catcher(entity) {
+                continue;
+            }
+            let Some(distance) = ray.intersect_plane(transform.translation(), InfinitePlane3d::new(transform.up()))
+            else {
+                continue;
+            };
+            let local = transform.affine().inverse().transform_point3(ray.get_point(distance));
+            if catcher
+                .bounds
+                .is_some_and(|bounds| local.x.abs() > bounds.x || local.z.abs() > bounds.y)
+            {
+                continue;
+            }
+            hits.push((entity, distance));
+        }
+
+        let priority = |entity: Entity| catchers.get(entity).map_or(0, |(_, _, catcher)| catcher.priority);
+        hits.sort_by(|a, b| priority(b.0).cmp(&priority(a.0)).then(a.1.total_cmp(&b.1)));
+        hits.into_iter()
+            .filter_map(|(entity, distance)| {
+                let (_, _, catcher) = catchers.get(entity).ok()?;
+                Some(ClickHit {
+                    pos: ray.get_point(distance) + catcher.offset,
+                    layers: catcher.layers,
+                })
+            })
+            .collect()
+    }
+}
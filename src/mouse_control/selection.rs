@@ -0,0 +1,179 @@
+#[cfg(feature = "ui_picking")]
+use crate::mouse_control::ui::UiBlock;
+use crate::mouse_control::touch::PointerInput;
+use crate::mouse_control::{
+    CursorCamera,
+    MouseMovementObject,
+};
+use bevy::math::{
+    Rect,
+    Vec2,
+};
+use bevy::prelude::{
+    ButtonInput,
+    Commands,
+    Component,
+    Entity,
+    GlobalTransform,
+    KeyCode,
+    MouseButton,
+    Query,
+    Res,
+    ResMut,
+    Resource,
+    With,
+};
+
+/// Mover which responds to move clicks only when [`Selected`]
+#[derive(Component, Default, Clone, Copy, Debug)]
+pub struct Selectable;
+
+/// Selectable mover is selected
+#[derive(Component, Default, Clone, Copy, Debug)]
+pub struct Selected;
+
+/// Select units by click or drag box.
+/// Use a different button from `MouseMovementObject::click_button`, e.g. left to select and right to move.
+/// If they are the same, selected units are moved when the button is released on the ground without a drag box,
+/// instead of being deselected.
+#[derive(Resource)]
+pub struct Selection {
+    /// Button to select. Default `MouseButton::Left`.
+    pub button: MouseButton,
+    /// Hold any of these keys to add to the selection. Default shift keys.
+    pub add_keys: Vec<KeyCode>,
+    /// Max distance in pixels from the cursor to the unit to select it by click
+    pub pick_radius: f32,
+    /// Min distance in pixels the cursor moves to select by drag box
+    pub drag_threshold: f32,
+    drag_start: Option<Vec2>,
+    /// Camera where the drag started
+    camera: Option<Entity>,
+    cursor: Option<Vec2>,
+    hovered: Option<Entity>,
+    is_ground_click: bool,
+}
+
+impl Default for Selection {
+    fn default() -> Self {
+        Self {
+            button: MouseButton::Left,
+            add_keys: vec![KeyCode::ShiftLeft, KeyCode::ShiftRight],
+            pick_radius: 16.,
+            drag_threshold: 4.,
+            drag_start: None,
+            camera: None,
+            cursor: None,
+            hovered: None,
+            is_ground_click: false,
+        }
+    }
+}
+
+impl Selection {
+    /// Drag box in viewport coordinates, for drawing it
+    pub fn drag_rect(&self) -> Option<Rect> {
+        let (start, cursor) = self.drag_start.zip(self.cursor)?;
+        (start.distance(cursor) >= self.drag_threshold).then(|| Rect::from_corners(start, cursor))
+    }
+
+    /// Selectable unit under the cursor
+    pub fn hovered(&self) -> Option<Entity> {
+        self.hovered
+    }
+
+    /// The button is released in this frame without a drag box or a unit under the cursor
+    pub fn is_ground_click(&self) -> bool {
+        self.is_ground_click
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn select(
+    mut commands: Commands,
+    mut selection: ResMut<Selection>,
+    pointer: Res<PointerInput>,
+    keys: Res<ButtonInput<KeyCode>>,
+    cursor_camera: CursorCamera,
+    units: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            Option<&MouseMovementObject>,
+            Option<&Selected>,
+        ),
+        With<Selectable>,
+    >,
+    #[cfg(feature = "ui_picking")] ui_block: UiBlock,
+) {
+    let mouse_btn = &pointer.buttons;
+    selection.is_ground_click = false;
+    let pick = cursor_camera.pick(&pointer);
+    // Keep using the camera where the drag started
+    if selection.drag_start.is_none() || mouse_btn.just_pressed(selection.button) {
+        selection.camera = pick.map(|(camera, _)| camera);
+    }
+    let Some((camera, camera_transform)) = selection.camera.and_then(|camera| cursor_camera.get(camera)) else {
+        selection.cursor = None;
+        selection.hovered = None;
+        return;
+    };
+    let cursor = pick.map(|(_, cursor)| cursor);
+    // Position of unit on screen
+    let projected =
+        |transform: &GlobalTransform| camera.world_to_viewport(camera_transform, transform.translation()).ok();
+
+    selection.cursor = cursor;
+    selection.hovered = cursor.and_then(|cursor| {
+        units
+            .iter()
+            .filter_map(|(entity, transform, ..)| Some((entity, projected(transform)?.distance(cursor))))
+            .filter(|(_, distance)| *distance <= selection.pick_radius)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(entity, _)| entity)
+    });
+
+    // Click on UI
+    #[cfg(feature = "ui_picking")]
+    let cursor = cursor.filter(|_| !mouse_btn.just_pressed(selection.button) || !ui_block.is_blocked());
+
+    if mouse_btn.just_pressed(selection.button) {
+        selection.drag_start = cursor;
+    }
+    if !mouse_btn.just_released(selection.button) {
+        return;
+    }
+
+    let rect = selection.drag_rect();
+    if selection.drag_start.take().is_none() {
+        return;
+    }
+    selection.is_ground_click = rect.is_none() && selection.hovered.is_none();
+    let picked: Vec<Entity> = match (rect, selection.hovered) {
+        (Some(rect), _) => units
+            .iter()
+            .filter(|(_, transform, ..)| projected(transform).is_some_and(|pos| rect.contains(pos)))
+            .map(|(entity, ..)| entity)
+            .collect(),
+        (None, Some(entity)) => vec![entity],
+        (None, None) => Vec::new(),
+    };
+
+    // Clicking on the ground with the move button is a move order, not deselect
+    let is_move_click = picked.is_empty()
+        && rect.is_none()
+        && units.iter().any(|(_, _, mv_object, selected)| {
+            selected.is_some() && mv_object.is_some_and(|mv_object| mv_object.click_button.contains(&selection.button))
+        });
+    let is_adding = keys.any_pressed(selection.add_keys.iter().copied());
+    if !is_adding && !is_move_click {
+        for (entity, _, _, selected) in units.iter() {
+            if selected.is_some() && !picked.contains(&entity) {
+                commands.entity(entity).remove::<Selected>();
+            }
+        }
+    }
+    for entity in picked {
+        commands.entity(entity).insert(Selected);
+    }
+}
@@ -0,0 +1,140 @@
+use crate::mouse_control::{
+    CursorCamera,
+    OrderModifiers,
+    OrderQueue,
+};
+use bevy::input::touch::Touches;
+use bevy::math::Vec2;
+use bevy::prelude::{
+    ButtonInput,
+    Entity,
+    KeyCode,
+    MouseButton,
+    Res,
+    ResMut,
+    Resource,
+    Time,
+};
+
+/// Map touch gestures to mouse buttons, so that touches select and move objects like clicks
+#[derive(Resource)]
+pub struct TouchControl {
+    /// Button pressed by tapping. Default `MouseButton::Left`.
+    pub tap: MouseButton,
+    /// Button pressed by tapping with two fingers. Default `MouseButton::Right`.
+    pub two_finger_tap: MouseButton,
+    /// Seconds holding one finger to press `tap` button with goal chained
+    pub long_press: f32,
+    /// Max distance in pixels a finger moves in a tap. Farther moves are left to camera panning or zooming.
+    pub tap_distance: f32,
+    gesture: Option<Gesture>,
+}
+
+impl Default for TouchControl {
+    fn default() -> Self {
+        Self {
+            tap: MouseButton::Left,
+            two_finger_tap: MouseButton::Right,
+            long_press: 0.5,
+            tap_distance: 16.,
+            gesture: None,
+        }
+    }
+}
+
+/// Touches from the first finger down to the last finger up
+struct Gesture {
+    started: f32,
+    max_fingers: usize,
+    is_moved: bool,
+    is_consumed: bool,
+    /// Center of fingers at the last frame
+    center: Vec2,
+}
+
+/// Buttons and position of mouse cursor or touch, in window coordinates
+#[derive(Resource, Default)]
+pub(crate) struct PointerInput {
+    pub(crate) position: Option<(Entity, Vec2)>,
+    pub(crate) buttons: ButtonInput<MouseButton>,
+    /// Decided by modifier keys, or long press
+    pub(crate) queue: OrderQueue,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn update_pointer_input(
+    mut pointer: ResMut<PointerInput>,
+    mut touch_control: ResMut<TouchControl>,
+    mouse_btn: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    modifiers: Res<OrderModifiers>,
+    touches: Res<Touches>,
+    cursor_camera: CursorCamera,
+    time: Res<Time>,
+) {
+    pointer.buttons.clear();
+    pointer.queue = if keys.any_pressed(modifiers.prepend.iter().copied()) {
+        OrderQueue::Prepend
+    } else if keys.any_pressed(modifiers.append.iter().copied()) {
+        OrderQueue::Append
+    } else {
+        OrderQueue::Replace
+    };
+    pointer.position = cursor_camera.cursor();
+    for button in mouse_btn.get_just_pressed() {
+        pointer.buttons.press(*button);
+    }
+    for button in mouse_btn.get_just_released() {
+        pointer.buttons.release(*button);
+    }
+
+    let fingers: Vec<Vec2> = touches.iter().map(|touch| touch.position()).collect();
+    let now = time.elapsed_secs();
+    if touch_control.gesture.is_none() && !fingers.is_empty() {
+        touch_control.gesture = Some(Gesture {
+            started: now,
+            max_fingers: 0,
+            is_moved: false,
+            is_consumed: false,
+            center: Vec2::ZERO,
+        });
+    }
+
+    let touch_control = touch_control.into_inner();
+    let Some(current) = &mut touch_control.gesture else {
+        return;
+    };
+    let window = cursor_camera.primary_window();
+
+    if !fingers.is_empty() {
+        current.max_fingers = current.max_fingers.max(fingers.len());
+        current.is_moved |= touches
+            .iter()
+            .any(|touch| touch.distance().length() > touch_control.tap_distance);
+        current.center = fingers.iter().sum::<Vec2>() / fingers.len() as f32;
+    }
+    let is_tap = !current.is_moved && !current.is_consumed;
+
+    // Long press
+    if is_tap && current.max_fingers == 1 && now - current.started >= touch_control.long_press {
+        current.is_consumed = true;
+        pointer.position = window.map(|window| (window, current.center));
+        pointer.buttons.press(touch_control.tap);
+        pointer.buttons.release(touch_control.tap);
+        pointer.queue = OrderQueue::Append;
+    }
+
+    if fingers.is_empty() {
+        let button = match current.max_fingers {
+            1 => Some(touch_control.tap),
+            2 => Some(touch_control.two_finger_tap),
+            _ => None,
+        };
+        if let Some(button) = button.filter(|_| is_tap) {
+            pointer.position = window.map(|window| (window, current.center));
+            pointer.buttons.press(button);
+            pointer.buttons.release(button);
+        }
+        touch_control.gesture = None;
+    }
+}
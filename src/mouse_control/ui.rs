@@ -0,0 +1,55 @@
+use crate::mouse_control::ClickCatcher;
+use bevy::ecs::system::SystemParam;
+use bevy::picking::hover::HoverMap;
+use bevy::picking::Pickable;
+use bevy::prelude::{
+    Component,
+    Or,
+    Query,
+    Res,
+    With,
+    Without,
+};
+use bevy::ui::{
+    Interaction,
+    Node,
+};
+
+/// UI node or pickable entity which lets clicks through to move objects
+#[derive(Component, Default, Clone, Copy, Debug)]
+pub struct ClickThrough;
+
+/// UI nodes and pickable entities under the cursor consume clicks
+#[allow(clippy::type_complexity)]
+#[derive(SystemParam)]
+pub(crate) struct UiBlock<'w, 's> {
+    hover_map: Option<Res<'w, HoverMap>>,
+    interactions: Query<'w, 's, &'static Interaction, (With<Node>, Without<ClickThrough>)>,
+    blockers: Query<
+        'w,
+        's,
+        (),
+        (
+            Or<(With<Node>, With<Pickable>)>,
+            Without<ClickThrough>,
+            Without<ClickCatcher>,
+        ),
+    >,
+}
+
+impl UiBlock<'_, '_> {
+    /// Cursor is on something which consumes the click
+    pub(crate) fn is_blocked(&self) -> bool {
+        let is_hovered = self.hover_map.as_ref().is_some_and(|hover_map| {
+            hover_map
+                .values()
+                .flatten()
+                .any(|(entity, _)| self.blockers.contains(*entity))
+        });
+        is_hovered
+            || self
+                .interactions
+                .iter()
+                .any(|interaction| *interaction != Interaction::None)
+    }
+}
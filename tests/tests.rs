@@ -1 +1,144 @@
+#![cfg(feature = "path_finding")]
 
+use bevy::input::InputPlugin;
+use bevy::prelude::*;
+use bevy::state::app::StatesPlugin;
+use bevy::time::TimeUpdateStrategy;
+use bevy_movement::grid::link::{
+    GridLink,
+    GridLinks,
+    GridPos,
+    LinkRoute,
+    LinkTraversal,
+};
+use bevy_movement::linear::{
+    GridInfo,
+    LinearMovement,
+};
+use bevy_movement::MovementPluginAnyState;
+use bevy_northstar::prelude::{
+    AgentOfGrid,
+    AgentPos,
+    CardinalGrid,
+    CardinalNeighborhood,
+    GridSettingsBuilder,
+    Nav,
+    NorthstarPlugin,
+    Pathfind,
+    PathfindMode,
+};
+use std::time::Duration;
+
+fn app() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, StatesPlugin, InputPlugin));
+    #[cfg(any(feature = "gizmos", feature = "mesh_picking"))]
+    app.add_plugins(AssetPlugin::default()).init_asset::<Mesh>();
+    #[cfg(feature = "gizmos")]
+    app.add_plugins(bevy::gizmos::GizmoPlugin);
+    app.add_plugins(NorthstarPlugin::<CardinalNeighborhood>::default())
+        .add_plugins(MovementPluginAnyState::any())
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(50)));
+    app
+}
+
+/// 8x8 grid at `offset` with impassable tiles
+fn spawn_grid(app: &mut App, offset: Vec3, blocked: &[UVec3]) -> Entity {
+    let mut grid = CardinalGrid::new(&GridSettingsBuilder::new_2d(8, 8).chunk_size(4).build());
+    for tile in blocked {
+        grid.set_nav(*tile, Nav::Impassable);
+    }
+    grid.build();
+    let mut grid_info = GridInfo::default();
+    grid_info.tile_size = Vec3::ONE;
+    grid_info.grid_offset = offset;
+    app.world_mut().spawn((grid, grid_info)).id()
+}
+
+fn spawn_agent(app: &mut App, grid: Entity, offset: Vec3, start: UVec3, order: impl Bundle) -> Entity {
+    app.world_mut()
+        .spawn((
+            LinearMovement {
+                speed: 10.,
+                ..default()
+            },
+            Transform::from_translation(start.as_vec3() + offset),
+            AgentPos(start),
+            AgentOfGrid(grid),
+            order,
+        ))
+        .id()
+}
+
+#[test]
+fn path_finding_on_two_grids() {
+    let mut app = app();
+    let offset_1 = Vec3::ZERO;
+    let offset_2 = Vec3::new(100., 0., 0.);
+    // Wall between x = 0 and x = 2, open at the bottom row on grid 1 and at the top row on grid 2
+    let wall_1: Vec<UVec3> = (1..8).map(|y| UVec3::new(1, y, 0)).collect();
+    let wall_2: Vec<UVec3> = (0..7).map(|y| UVec3::new(1, y, 0)).collect();
+    let grid_1 = spawn_grid(&mut app, offset_1, &wall_1);
+    let grid_2 = spawn_grid(&mut app, offset_2, &wall_2);
+
+    let (start, goal) = (UVec3::new(0, 0, 0), UVec3::new(2, 0, 0));
+    let order = || Pathfind::new(goal).mode(PathfindMode::AStar);
+    let agent_1 = spawn_agent(&mut app, grid_1, offset_1, start, order());
+    let agent_2 = spawn_agent(&mut app, grid_2, offset_2, start, order());
+
+    let mut visited_1 = vec![start];
+    let mut visited_2 = vec![start];
+    for _ in 0..500 {
+        app.update();
+        let world = app.world();
+        for (agent, visited) in [(agent_1, &mut visited_1), (agent_2, &mut visited_2)] {
+            let pos = world.get::<AgentPos>(agent).unwrap().0;
+            if visited.last() != Some(&pos) {
+                visited.push(pos);
+            }
+        }
+        if visited_1.last() == Some(&goal) && visited_2.last() == Some(&goal) {
+            break;
+        }
+    }
+
+    // Each agent walks on its own grid
+    assert_eq!(visited_1, vec![start, UVec3::new(1, 0, 0), goal]);
+    assert_eq!(visited_2.last(), Some(&goal));
+    assert_eq!(visited_2.len(), 17);
+    assert!(visited_2.iter().all(|tile| !wall_2.contains(tile)));
+
+    let translation = app.world().get::<Transform>(agent_2).unwrap().translation;
+    assert!(translation.distance(goal.as_vec3() + offset_2) < 1e-3);
+}
+
+#[test]
+fn link_route_between_grids() {
+    let mut app = app();
+    let offset_1 = Vec3::ZERO;
+    let offset_2 = Vec3::new(100., 0., 0.);
+    let grid_1 = spawn_grid(&mut app, offset_1, &[]);
+    let grid_2 = spawn_grid(&mut app, offset_2, &[]);
+    let link = app.world_mut().resource_mut::<GridLinks>().add(GridLink {
+        from: GridPos::new(Some(grid_1), UVec3::new(3, 0, 0)),
+        to: GridPos::new(Some(grid_2), UVec3::new(0, 3, 0)),
+        cost: 1,
+        traversal: LinkTraversal::Teleport,
+    });
+
+    let goal = UVec3::new(2, 5, 0);
+    let order = LinkRoute::new(vec![link], goal);
+    let agent = spawn_agent(&mut app, grid_1, offset_1, UVec3::ZERO, order);
+    for _ in 0..500 {
+        app.update();
+        if app.world().get::<LinkRoute>(agent).is_none() && app.world().get::<Pathfind>(agent).is_none() {
+            break;
+        }
+    }
+
+    let world = app.world();
+    assert_eq!(world.get::<AgentOfGrid>(agent).map(|grid| grid.0), Some(grid_2));
+    assert_eq!(world.get::<AgentPos>(agent).map(|pos| pos.0), Some(goal));
+    let translation = world.get::<Transform>(agent).unwrap().translation;
+    assert!(translation.distance(goal.as_vec3() + offset_2) < 1e-3);
+}